@@ -0,0 +1,115 @@
+use std::{env, fs, path::PathBuf};
+
+use asefile::AsepriteFile;
+
+// Aseprite tags that get drawn into the background instead of being sprites,
+// see src/stamp.rs.
+const STAMPS: &[(&str, &str)] = &[
+    ("gfx/houses.aseprite", "House-1"),
+    ("gfx/pause.aseprite", "Pause"),
+    ("gfx/title-prompt.aseprite", "Press-Start"),
+    ("gfx/title-new-game.aseprite", "New-Game"),
+    ("gfx/title-continue.aseprite", "Continue"),
+];
+
+// Sprites that are part of the world and get tinted with it, and the static in
+// src/tiles.rs they're included as, see src/daynight.rs. Menus and the like are
+// left out so they keep their colours.
+const WORLD_SPRITES: &[(&str, &str)] = &[
+    ("gfx/char-front.aseprite", "GRAPHICS"),
+    ("gfx/ball.aseprite", "BALL_GRAPHICS"),
+    ("gfx/props.aseprite", "PROPS"),
+];
+
+// Writes the first frame of each tag to OUT_DIR as a png for
+// include_background_gfx, plus stamps.rs with the size of each in tiles, and
+// world_sprites.rs with a copy of the world sprites to tint.
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let mut layouts = vec![];
+
+    for &(path, name) in STAMPS {
+        println!("cargo:rerun-if-changed={path}");
+
+        let file = AsepriteFile::read_file(path.as_ref()).unwrap();
+        let tag = file
+            .tag_by_name(name)
+            .unwrap_or_else(|| panic!("no tag called {name} in {path}"));
+
+        let (width, height) = file.size();
+        assert!(
+            width.is_multiple_of(8) && height.is_multiple_of(8),
+            "{path} isn't a whole number of tiles"
+        );
+
+        file.frame(tag.from_frame())
+            .image()
+            .save(out_dir.join(format!("{name}.png")))
+            .unwrap();
+
+        layouts.push(format!("({name:?}, ({}, {}))", width / 8, height / 8));
+    }
+
+    fs::write(
+        out_dir.join("stamps.rs"),
+        format!("&[{}]", layouts.join(", ")),
+    )
+    .unwrap();
+
+    let mut sheets = vec![];
+
+    for &(path, name) in WORLD_SPRITES {
+        println!("cargo:rerun-if-changed={path}");
+
+        // Transparent first, then every colour in the order it turns up.
+        let mut palette = vec![0u16];
+        let mut frames = vec![];
+
+        let file = AsepriteFile::read_file(path.as_ref()).unwrap();
+        for frame in 0..file.num_frames() {
+            let pixels: Vec<String> = file
+                .frame(frame)
+                .image()
+                .pixels()
+                .map(|pixel| {
+                    let [r, g, b, a] = pixel.0.map(u16::from);
+                    if a < 255 {
+                        return 0;
+                    }
+
+                    // The same conversion as agb's.
+                    let colour = (r >> 3) | (g >> 3) << 5 | (b >> 3) << 10;
+                    match palette.iter().skip(1).position(|&seen| seen == colour) {
+                        Some(index) => index + 1,
+                        None => {
+                            palette.push(colour);
+                            palette.len() - 1
+                        }
+                    }
+                })
+                .map(|index| index.to_string())
+                .collect();
+
+            frames.push(format!("&[{}]", pixels.join(", ")));
+        }
+
+        assert!(palette.len() <= 16, "{path} has more than 15 colours");
+        palette.resize(16, 0);
+
+        let palette: Vec<_> = palette
+            .iter()
+            .map(|colour| format!("{colour:#06x}"))
+            .collect();
+        sheets.push(format!(
+            "Sheet {{ graphics: crate::tiles::{name}, palette: Palette16::new([{}]), frames: &[{}] }}",
+            palette.join(", "),
+            frames.join(", ")
+        ));
+    }
+
+    fs::write(
+        out_dir.join("world_sprites.rs"),
+        format!("&[{}]", sheets.join(", ")),
+    )
+    .unwrap();
+}
@@ -0,0 +1,80 @@
+// A map tile whose graphics cycle through `frames` of another tileset, moving on
+// every `period` frames. Made by mapgen's generate_tile_animations.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TileAnimation {
+    pub tile: usize,
+    pub frames: &'static [usize],
+    pub period: u16,
+}
+
+// Keeps time for a set of tile animations and says which tile graphics need
+// swapping, leaving the map itself alone.
+#[derive(Clone, Debug)]
+pub struct Animator {
+    animations: &'static [TileAnimation],
+    frame: u32,
+}
+
+impl Animator {
+    pub fn new(animations: &'static [TileAnimation]) -> Self {
+        Self {
+            animations,
+            frame: 0,
+        }
+    }
+
+    // The `(tile, frame)` pairs to swap in this frame, every animation shows its
+    // first frame on the first tick.
+    pub fn tick(&mut self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let frame = self.frame;
+        self.frame = self.frame.wrapping_add(1);
+
+        self.animations.iter().filter_map(move |animation| {
+            let period = animation.period.max(1) as u32;
+            if !frame.is_multiple_of(period) {
+                return None;
+            }
+
+            let step = (frame / period) as usize % animation.frames.len();
+            Some((animation.tile, animation.frames[step]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    const ANIMATIONS: &[TileAnimation] = &[
+        TileAnimation {
+            tile: 10,
+            frames: &[0, 1, 2],
+            period: 2,
+        },
+        TileAnimation {
+            tile: 11,
+            frames: &[3, 4],
+            period: 3,
+        },
+    ];
+
+    #[test]
+    fn tiles_move_on_each_period_and_loop() {
+        let mut animator = Animator::new(ANIMATIONS);
+        let swaps: Vec<Vec<_>> = (0..7).map(|_| animator.tick().collect()).collect();
+
+        assert_eq!(
+            swaps,
+            [
+                vec![(10, 0), (11, 3)],
+                vec![],
+                vec![(10, 1)],
+                vec![(11, 4)],
+                vec![(10, 2)],
+                vec![],
+                vec![(10, 0), (11, 3)],
+            ]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use agb_fixnum::{Rect, Vector2D};
+
+use crate::{
+    map::{tile_at, TILE_SIZE},
+    terrain::{attributes, Rules, Terrain},
+};
+
+pub const BODY_OFFSET: (i32, i32) = (8, 8);
+pub const BODY_SIZE: (i32, i32) = (24, 24);
+
+const WALL_TILE: usize = 1;
+
+pub fn body(position: Vector2D<i32>) -> Rect<i32> {
+    Rect::new(position + BODY_OFFSET.into(), BODY_SIZE.into())
+}
+
+pub fn center(area: Rect<i32>) -> Vector2D<i32> {
+    area.position + area.size / 2
+}
+
+fn enterable<const W: usize, const H: usize>(
+    tilemap: &[[usize; W]; H],
+    tile: (i16, i16),
+    direction: (i32, i32),
+    rules: Rules,
+) -> bool {
+    tile_at(tilemap, (tile.0 as i32, tile.1 as i32))
+        .is_some_and(|tile| attributes(tile).enterable(direction, rules))
+}
+
+// The path_*_blocked checks take the world position of the top left of the
+// sprite and only look at the map when it lines up with the tile grid.
+pub fn path_right_blocked<const W: usize, const H: usize>(
+    (x, y): (i16, i16),
+    tilemap: &[[usize; W]; H],
+    rules: Rules,
+) -> bool {
+    if x.rem_euclid(TILE_SIZE as i16) != 0 {
+        return false;
+    }
+
+    let next_tile = x.div_euclid(TILE_SIZE as i16) + 3;
+    let cur_tile_y = y.div_euclid(TILE_SIZE as i16);
+
+    (1..=3).any(|row| !enterable(tilemap, (next_tile, cur_tile_y + row), (1, 0), rules))
+}
+
+pub fn path_left_blocked<const W: usize, const H: usize>(
+    (x, y): (i16, i16),
+    tilemap: &[[usize; W]; H],
+    rules: Rules,
+) -> bool {
+    if x.rem_euclid(TILE_SIZE as i16) != 0 {
+        return false;
+    }
+
+    let last_tile = x.div_euclid(TILE_SIZE as i16);
+    let cur_tile_y = y.div_euclid(TILE_SIZE as i16);
+
+    (1..=3).any(|row| !enterable(tilemap, (last_tile, cur_tile_y + row), (-1, 0), rules))
+}
+
+pub fn path_up_blocked<const W: usize, const H: usize>(
+    (x, y): (i16, i16),
+    tilemap: &[[usize; W]; H],
+    rules: Rules,
+) -> bool {
+    if y.rem_euclid(TILE_SIZE as i16) != 0 {
+        return false;
+    }
+
+    let last_tile = y.div_euclid(TILE_SIZE as i16);
+    let cur_tile_x = x.div_euclid(TILE_SIZE as i16);
+
+    (1..=3).any(|column| !enterable(tilemap, (cur_tile_x + column, last_tile), (0, -1), rules))
+}
+
+pub fn path_down_blocked<const W: usize, const H: usize>(
+    (x, y): (i16, i16),
+    tilemap: &[[usize; W]; H],
+    rules: Rules,
+) -> bool {
+    if y.rem_euclid(TILE_SIZE as i16) != 0 {
+        return false;
+    }
+
+    let next_tile = y.div_euclid(TILE_SIZE as i16) + 3;
+    let cur_tile_x = x.div_euclid(TILE_SIZE as i16);
+
+    (1..=3).any(|column| !enterable(tilemap, (cur_tile_x + column, next_tile), (0, 1), rules))
+}
+
+// Whether the sprite at `(x, y)`, lined up with the grid, is about to step down
+// onto a ledge. Like path_down_blocked it looks at the bottom row of the body.
+pub fn ledge_ahead<const W: usize, const H: usize>(
+    (x, y): (i16, i16),
+    tilemap: &[[usize; W]; H],
+) -> bool {
+    if y.rem_euclid(TILE_SIZE as i16) != 0 {
+        return false;
+    }
+
+    let next_tile = y.div_euclid(TILE_SIZE as i16) + 3;
+    let cur_tile_x = x.div_euclid(TILE_SIZE as i16);
+
+    (1..=3).any(|column| {
+        tile_at(tilemap, ((cur_tile_x + column) as i32, next_tile as i32))
+            .is_some_and(|tile| attributes(tile).terrain == Terrain::Ledge)
+    })
+}
+
+// Whether moving the sprite at `(x, y)` one pixel along a single axis runs into
+// the map or into anything `occupied` says is in the way of the body.
+pub fn step_blocked<const W: usize, const H: usize>(
+    (x, y): (i16, i16),
+    (dx, dy): (i16, i16),
+    tilemap: &[[usize; W]; H],
+    rules: Rules,
+    occupied: &impl Fn(Rect<i32>) -> bool,
+) -> bool {
+    let next = (x + dx, y + dy);
+
+    let wall = match (dx, dy) {
+        (1, 0) => path_right_blocked(next, tilemap, rules),
+        (-1, 0) => path_left_blocked(next, tilemap, rules),
+        (0, 1) => path_down_blocked(next, tilemap, rules),
+        (0, -1) => path_up_blocked(next, tilemap, rules),
+        _ => false,
+    };
+
+    wall || occupied(body(Vector2D::new(next.0 as i32, next.1 as i32)))
+}
+
+// Used by the scrolling prototype in app_ret, where the player sits around tile
+// (15, 10) of the screen and anything off the level counts as a wall.
+pub fn check_boundary<const W: usize, const H: usize>(
+    (scroll_x, scroll_y): (i16, i16),
+    level: &[[usize; W]; H],
+) -> bool {
+    let x_offset = scroll_x.div_euclid(TILE_SIZE as i16) as i32;
+    let y_offset = scroll_y.div_euclid(TILE_SIZE as i16) as i32;
+
+    [(15, 10), (14, 10), (15, 12), (15, 8)]
+        .iter()
+        .any(|&(x, y)| {
+            tile_at(level, (x + x_offset, y + y_offset)).is_none_or(|tile| tile == WALL_TILE)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALKING: Rules = Rules { swimming: false };
+
+    // 6x6 tiles of grass surrounded by a one tile wall.
+    fn room() -> [[usize; 8]; 8] {
+        let mut tilemap = [[0; 8]; 8];
+
+        for row in tilemap.iter_mut().take(7).skip(1) {
+            for tile in row.iter_mut().take(7).skip(1) {
+                *tile = 5;
+            }
+        }
+
+        tilemap
+    }
+
+    #[test]
+    fn body_is_offset_into_the_sprite() {
+        let body = body((16, 24).into());
+
+        assert_eq!(body.position, (24, 32).into());
+        assert_eq!(body.size, (24, 24).into());
+        assert_eq!(center(body), (36, 44).into());
+    }
+
+    #[test]
+    fn paths_are_only_checked_on_the_tile_grid() {
+        let tilemap = [[0; 8]; 8];
+
+        assert!(!path_right_blocked((1, 0), &tilemap, WALKING));
+        assert!(!path_left_blocked((-1, 0), &tilemap, WALKING));
+        assert!(!path_up_blocked((0, 3), &tilemap, WALKING));
+        assert!(!path_down_blocked((0, -3), &tilemap, WALKING));
+    }
+
+    #[test]
+    fn paths_inside_the_room_are_free() {
+        let tilemap = room();
+
+        assert!(!path_right_blocked((8, 0), &tilemap, WALKING));
+        assert!(!path_left_blocked((8, 0), &tilemap, WALKING));
+        assert!(!path_up_blocked((0, 8), &tilemap, WALKING));
+        assert!(!path_down_blocked((0, 8), &tilemap, WALKING));
+    }
+
+    #[test]
+    fn walls_block_paths() {
+        let tilemap = room();
+
+        assert!(path_right_blocked((32, 0), &tilemap, WALKING));
+        assert!(path_left_blocked((0, 0), &tilemap, WALKING));
+        assert!(path_up_blocked((0, 0), &tilemap, WALKING));
+        assert!(path_down_blocked((0, 32), &tilemap, WALKING));
+    }
+
+    #[test]
+    fn paths_off_the_map_are_blocked_instead_of_panicking() {
+        let tilemap = room();
+
+        assert!(path_left_blocked((-8, 0), &tilemap, WALKING));
+        assert!(path_up_blocked((0, -8), &tilemap, WALKING));
+        assert!(path_right_blocked((i16::MIN, 0), &tilemap, WALKING));
+        assert!(path_down_blocked((0, i16::MIN), &tilemap, WALKING));
+        assert!(path_right_blocked((64, 0), &tilemap, WALKING));
+        assert!(path_down_blocked((0, 64), &tilemap, WALKING));
+    }
+
+    #[test]
+    fn ledges_block_every_way_but_down() {
+        let mut tilemap = room();
+        tilemap[4] = [0, 11, 11, 11, 11, 11, 11, 0];
+
+        assert!(ledge_ahead((0, 8), &tilemap));
+        assert!(!ledge_ahead((0, 9), &tilemap));
+        assert!(!ledge_ahead((0, 0), &tilemap));
+        assert!(!path_down_blocked((0, 8), &tilemap, WALKING));
+        assert!(path_up_blocked((0, 32), &tilemap, WALKING));
+        assert!(path_right_blocked((8, 16), &tilemap, WALKING));
+    }
+
+    #[test]
+    fn water_blocks_unless_swimming() {
+        let mut tilemap = room();
+        tilemap[4][3] = 10;
+
+        assert!(path_down_blocked((0, 8), &tilemap, WALKING));
+        assert!(!path_down_blocked(
+            (0, 8),
+            &tilemap,
+            Rules { swimming: true }
+        ));
+    }
+
+    #[test]
+    fn check_boundary_hits_walls() {
+        let mut level = [[0; 64]; 32];
+        assert!(!check_boundary((0, 0), &level));
+
+        level[10][15] = WALL_TILE;
+        assert!(check_boundary((0, 0), &level));
+        assert!(check_boundary((8, 0), &level));
+        assert!(!check_boundary((16, 0), &level));
+        assert!(check_boundary((0, -16), &level));
+        assert!(!check_boundary((-16, 0), &level));
+    }
+
+    #[test]
+    fn check_boundary_off_the_level_is_blocked() {
+        let level = [[0; 64]; 32];
+
+        assert!(check_boundary((-200, 0), &level));
+        assert!(check_boundary((0, -100), &level));
+        assert!(check_boundary((500, 0), &level));
+        assert!(check_boundary((0, 200), &level));
+    }
+}
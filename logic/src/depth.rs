@@ -0,0 +1,48 @@
+use agb_fixnum::Rect;
+
+// What kind of thing a sprite is, which decides who is in front when two
+// stand on the same row.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layer {
+    Prop,
+    Npc,
+    Player,
+}
+
+const LAYERS: i32 = 3;
+
+// agb draws objects with a lower z on top. Going by the bottom of the body puts
+// whatever stands further down the screen in front.
+pub fn z(body: Rect<i32>, layer: Layer) -> i32 {
+    -((body.position.y + body.size.y) * LAYERS + layer as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::body;
+
+    #[test]
+    fn lower_on_screen_is_in_front() {
+        let player = z(body((0, 40).into()), Layer::Player);
+        let npc = z(body((0, 41).into()), Layer::Npc);
+        let prop = z(body((0, 39).into()), Layer::Prop);
+
+        assert!(npc < player);
+        assert!(player < prop);
+    }
+
+    #[test]
+    fn ties_go_to_the_player_then_npcs() {
+        let at = |layer| z(body((0, 40).into()), layer);
+
+        assert!(at(Layer::Player) < at(Layer::Npc));
+        assert!(at(Layer::Npc) < at(Layer::Prop));
+        assert!(z(body((0, 40).into()), Layer::Prop) < z(body((0, 39).into()), Layer::Player));
+    }
+
+    #[test]
+    fn works_off_the_top_of_the_map() {
+        assert!(z(body((0, -10).into()), Layer::Npc) < z(body((0, -11).into()), Layer::Player));
+    }
+}
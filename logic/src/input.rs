@@ -0,0 +1,441 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+// Same bits as the GBA's KEYINPUT register and agb's `Button`, so a frame of
+// input is just the mask of what was held down.
+pub mod button {
+    pub const A: u16 = 1 << 0;
+    pub const B: u16 = 1 << 1;
+    pub const SELECT: u16 = 1 << 2;
+    pub const START: u16 = 1 << 3;
+    pub const RIGHT: u16 = 1 << 4;
+    pub const LEFT: u16 = 1 << 5;
+    pub const UP: u16 = 1 << 6;
+    pub const DOWN: u16 = 1 << 7;
+    pub const R: u16 = 1 << 8;
+    pub const L: u16 = 1 << 9;
+}
+
+// Stands in for agb's ButtonController when there's no hardware to read.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Buttons {
+    previous: u16,
+    current: u16,
+}
+
+impl Buttons {
+    pub fn update(&mut self, pressed: u16) {
+        self.previous = self.current;
+        self.current = pressed;
+    }
+
+    pub fn pressed(&self) -> u16 {
+        self.current
+    }
+
+    pub fn is_pressed(&self, buttons: u16) -> bool {
+        self.current & buttons != 0
+    }
+
+    pub fn is_just_pressed(&self, buttons: u16) -> bool {
+        self.current & !self.previous & buttons != 0
+    }
+
+    pub fn x_tri(&self) -> i32 {
+        tri(
+            self.is_pressed(button::LEFT),
+            self.is_pressed(button::RIGHT),
+        )
+    }
+
+    pub fn y_tri(&self) -> i32 {
+        tri(self.is_pressed(button::UP), self.is_pressed(button::DOWN))
+    }
+}
+
+fn tri(negative: bool, positive: bool) -> i32 {
+    positive as i32 - negative as i32
+}
+
+// How many frames a press is remembered for, so pressing a button just before
+// the game is ready for it still counts.
+pub const BUFFER_FRAMES: u8 = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Confirm,
+    Cancel,
+    Menu,
+    Run,
+    Save,
+}
+
+impl Action {
+    pub const COUNT: usize = 5;
+    pub const ALL: [Action; Action::COUNT] = [
+        Action::Confirm,
+        Action::Cancel,
+        Action::Menu,
+        Action::Run,
+        Action::Save,
+    ];
+}
+
+// What happens when two directions on different axes are held at once.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Diagonal {
+    // Move on both axes.
+    Both,
+    Horizontal,
+    Vertical,
+    // Whichever axis was pressed most recently, vertical if they went down on
+    // the same frame.
+    LastPressed,
+}
+
+impl Diagonal {
+    // The one after this, for flicking through them in the options.
+    pub fn next(self) -> Self {
+        match self {
+            Diagonal::Both => Diagonal::Horizontal,
+            Diagonal::Horizontal => Diagonal::Vertical,
+            Diagonal::Vertical => Diagonal::LastPressed,
+            Diagonal::LastPressed => Diagonal::Both,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Config {
+    pub bindings: [u16; Action::COUNT],
+    pub diagonal: Diagonal,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bindings: [
+                button::A,
+                button::B,
+                button::START,
+                button::B,
+                button::SELECT,
+            ],
+            diagonal: Diagonal::Both,
+        }
+    }
+}
+
+impl Config {
+    pub fn binding(&self, action: Action) -> u16 {
+        self.bindings[action as usize]
+    }
+
+    // If nothing else is on the old binding of `action`, the first other action
+    // already bound to `buttons` takes it over so that the button isn't left
+    // doing nothing. Any further actions on `buttons` keep sharing it.
+    pub fn bind(&mut self, action: Action, buttons: u16) {
+        let old = core::mem::replace(&mut self.bindings[action as usize], buttons);
+        if old == buttons || self.bindings.contains(&old) {
+            return;
+        }
+
+        if let Some(other) = Action::ALL
+            .into_iter()
+            .find(|&other| other != action && self.binding(other) == buttons)
+        {
+            self.bindings[other as usize] = old;
+        }
+    }
+}
+
+// Logical actions on top of the raw buttons, fed a mask each frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Input {
+    pub config: Config,
+    buttons: Buttons,
+    buffered: [u8; Action::COUNT],
+    vertical_last: bool,
+}
+
+impl Input {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            buttons: Buttons::default(),
+            buffered: [0; Action::COUNT],
+            vertical_last: false,
+        }
+    }
+
+    pub fn update(&mut self, pressed: u16) {
+        self.buttons.update(pressed);
+
+        for action in Action::ALL {
+            let buffered = &mut self.buffered[action as usize];
+
+            *buffered = if self.buttons.is_just_pressed(self.config.binding(action)) {
+                BUFFER_FRAMES
+            } else {
+                buffered.saturating_sub(1)
+            };
+        }
+
+        if self.buttons.is_just_pressed(button::UP | button::DOWN) {
+            self.vertical_last = true;
+        } else if self.buttons.is_just_pressed(button::LEFT | button::RIGHT) {
+            self.vertical_last = false;
+        }
+    }
+
+    pub fn buttons(&self) -> &Buttons {
+        &self.buttons
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.buttons.is_pressed(self.config.binding(action))
+    }
+
+    // Forgets buffered presses, so one meant for whatever was on screen before
+    // doesn't carry over.
+    pub fn clear(&mut self) {
+        self.buffered = [0; Action::COUNT];
+    }
+
+    // True once per press of `action` within the last BUFFER_FRAMES frames.
+    pub fn take(&mut self, action: Action) -> bool {
+        let buffered = core::mem::take(&mut self.buffered[action as usize]);
+
+        buffered > 0
+    }
+
+    // The d-pad as x and y of -1, 0 or 1, after the diagonal policy.
+    pub fn movement(&self) -> (i32, i32) {
+        let (x, y) = (self.buttons.x_tri(), self.buttons.y_tri());
+
+        if x == 0 || y == 0 {
+            return (x, y);
+        }
+
+        match self.config.diagonal {
+            Diagonal::Both => (x, y),
+            Diagonal::Horizontal => (x, 0),
+            Diagonal::Vertical => (0, y),
+            Diagonal::LastPressed if self.vertical_last => (0, y),
+            Diagonal::LastPressed => (x, 0),
+        }
+    }
+}
+
+// Input for a run of frames, stored as how long each combination of buttons
+// was held. Written out as space separated `mask*frames` pairs with the mask
+// in hex, e.g. `10*24 0*8 40*16`, which is what ends up in the mgba log.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Recording {
+    runs: Vec<(u16, u16)>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, pressed: u16) {
+        match self.runs.last_mut() {
+            Some((mask, frames)) if *mask == pressed && *frames < u16::MAX => *frames += 1,
+            _ => self.runs.push((pressed, 1)),
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.runs.iter().map(|&(_, frames)| frames as usize).sum()
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = u16> + '_ {
+        self.runs
+            .iter()
+            .flat_map(|&(mask, frames)| core::iter::repeat_n(mask, frames as usize))
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut runs = Vec::new();
+
+        for run in text.split_whitespace() {
+            let (mask, frames) = run.split_once('*')?;
+            let mask = u16::from_str_radix(mask, 16).ok()?;
+            let frames = frames.parse().ok().filter(|&frames| frames > 0)?;
+
+            runs.push((mask, frames));
+        }
+
+        Some(Self { runs })
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (mask, frames)) in self.runs.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:x}*{}", mask, frames)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn tri_matches_agb() {
+        let mut buttons = Buttons::default();
+
+        buttons.update(button::LEFT | button::DOWN);
+        assert_eq!((buttons.x_tri(), buttons.y_tri()), (-1, 1));
+
+        buttons.update(button::LEFT | button::RIGHT | button::UP);
+        assert_eq!((buttons.x_tri(), buttons.y_tri()), (0, -1));
+    }
+
+    #[test]
+    fn just_pressed_only_lasts_a_frame() {
+        let mut buttons = Buttons::default();
+
+        buttons.update(button::A);
+        assert!(buttons.is_just_pressed(button::A));
+
+        buttons.update(button::A);
+        assert!(buttons.is_pressed(button::A));
+        assert!(!buttons.is_just_pressed(button::A));
+    }
+
+    #[test]
+    fn presses_are_buffered_until_taken() {
+        let mut input = Input::new(Config::default());
+
+        input.update(button::A);
+        input.update(0);
+        input.update(0);
+        assert!(input.take(Action::Confirm));
+        assert!(!input.take(Action::Confirm));
+
+        input.update(button::A);
+        for _ in 0..BUFFER_FRAMES {
+            input.update(0);
+        }
+        assert!(!input.take(Action::Confirm));
+    }
+
+    #[test]
+    fn one_button_can_serve_several_actions() {
+        let mut input = Input::new(Config::default());
+
+        input.update(button::B);
+        assert!(input.is_held(Action::Run));
+        assert!(input.take(Action::Cancel));
+        assert!(!input.is_held(Action::Confirm));
+    }
+
+    #[test]
+    fn rebinding_swaps_with_whatever_had_the_button() {
+        let mut config = Config::default();
+        config.bind(Action::Menu, button::SELECT);
+
+        assert_eq!(config.binding(Action::Menu), button::SELECT);
+        assert_eq!(config.binding(Action::Save), button::START);
+    }
+
+    #[test]
+    fn rebinding_onto_a_shared_button_swaps_only_one_action() {
+        let mut config = Config::default();
+        config.bind(Action::Confirm, button::B);
+
+        assert_eq!(config.binding(Action::Confirm), button::B);
+        assert_eq!(config.binding(Action::Cancel), button::A);
+        assert_eq!(config.binding(Action::Run), button::B);
+        assert_eq!(config.binding(Action::Menu), button::START);
+
+        let mut input = Input::new(config);
+        input.update(button::B);
+        assert!(input.take(Action::Confirm));
+        assert!(input.is_held(Action::Run));
+        assert!(!input.take(Action::Cancel));
+    }
+
+    #[test]
+    fn rebinding_off_a_shared_button_swaps_nothing() {
+        let mut config = Config::default();
+        config.bind(Action::Run, button::A);
+
+        assert_eq!(config.binding(Action::Run), button::A);
+        assert_eq!(config.binding(Action::Confirm), button::A);
+        assert_eq!(config.binding(Action::Cancel), button::B);
+
+        config.bind(Action::Run, button::R);
+        config.bind(Action::Confirm, button::R);
+        assert_eq!(config.binding(Action::Confirm), button::R);
+        assert_eq!(config.binding(Action::Run), button::A);
+    }
+
+    #[test]
+    fn clearing_forgets_buffered_presses() {
+        let mut input = Input::new(Config::default());
+
+        input.update(button::A | button::START);
+        input.clear();
+        assert!(!input.take(Action::Confirm));
+        assert!(!input.take(Action::Menu));
+
+        input.update(0);
+        input.update(button::A);
+        assert!(input.take(Action::Confirm));
+    }
+
+    #[test]
+    fn diagonal_policies() {
+        let held = |diagonal, frames: &[u16]| {
+            let mut input = Input::new(Config {
+                diagonal,
+                ..Config::default()
+            });
+            for &pressed in frames {
+                input.update(pressed);
+            }
+            input.movement()
+        };
+        let both = button::RIGHT | button::UP;
+
+        assert_eq!(held(Diagonal::Both, &[both]), (1, -1));
+        assert_eq!(held(Diagonal::Horizontal, &[both]), (1, 0));
+        assert_eq!(held(Diagonal::Vertical, &[both]), (0, -1));
+        assert_eq!(held(Diagonal::LastPressed, &[both]), (0, -1));
+        assert_eq!(held(Diagonal::LastPressed, &[button::UP, both]), (1, 0));
+        assert_eq!(held(Diagonal::LastPressed, &[button::RIGHT, both]), (0, -1));
+        assert_eq!(held(Diagonal::LastPressed, &[both, button::RIGHT]), (1, 0));
+    }
+
+    #[test]
+    fn recordings_round_trip_through_text() {
+        let mut recording = Recording::new();
+        for pressed in [0x10, 0x10, 0x10, 0, 0x41, 0x41] {
+            recording.push(pressed);
+        }
+
+        assert_eq!(recording.to_string(), "10*3 0*1 41*2");
+        assert_eq!(recording.frame_count(), 6);
+        assert_eq!(Recording::parse("10*3 0*1 41*2"), Some(recording.clone()));
+        assert!(recording.frames().eq([0x10, 0x10, 0x10, 0, 0x41, 0x41]));
+    }
+
+    #[test]
+    fn malformed_recordings_are_rejected() {
+        assert_eq!(Recording::parse("10"), None);
+        assert_eq!(Recording::parse("10*0"), None);
+        assert_eq!(Recording::parse("zz*3"), None);
+        assert_eq!(Recording::parse(""), Some(Recording::new()));
+    }
+}
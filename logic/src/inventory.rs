@@ -0,0 +1,171 @@
+// Most of one item a slot holds.
+pub const MAX_STACK: u8 = 99;
+
+// A fixed number of slots, each holding a stack of one kind of item. New items
+// top up a stack of the same kind before taking an empty slot.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Inventory<T, const N: usize> {
+    slots: [Option<(T, u8)>; N],
+}
+
+impl<T: Copy + Eq, const N: usize> Default for Inventory<T, N> {
+    fn default() -> Self {
+        Self { slots: [None; N] }
+    }
+}
+
+impl<T: Copy + Eq, const N: usize> Inventory<T, N> {
+    pub fn slots(&self) -> &[Option<(T, u8)>; N] {
+        &self.slots
+    }
+
+    // Only for loading a save, doesn't check for split stacks.
+    pub fn from_slots(slots: [Option<(T, u8)>; N]) -> Self {
+        Self { slots }
+    }
+
+    pub fn count(&self, item: T) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|(held, _)| *held == item)
+            .map(|&(_, count)| count as u32)
+            .sum()
+    }
+
+    // Whether all `count` fit, nothing is added if they don't.
+    pub fn fits(&self, item: T, count: u8) -> bool {
+        let room: u32 = self
+            .slots
+            .iter()
+            .map(|slot| match slot {
+                Some((held, stack)) if *held == item => (MAX_STACK - stack) as u32,
+                Some(_) => 0,
+                None => MAX_STACK as u32,
+            })
+            .sum();
+
+        room >= count as u32
+    }
+
+    // Adds `count` of the item if they all fit and returns whether they did.
+    pub fn add(&mut self, item: T, count: u8) -> bool {
+        if !self.fits(item, count) {
+            return false;
+        }
+
+        let mut left = count;
+        for (held, stack) in self.slots.iter_mut().flatten() {
+            if *held == item {
+                let moved = left.min(MAX_STACK - *stack);
+                *stack += moved;
+                left -= moved;
+            }
+        }
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if left == 0 {
+                break;
+            }
+
+            let moved = left.min(MAX_STACK);
+            *slot = Some((item, moved));
+            left -= moved;
+        }
+
+        true
+    }
+
+    // Takes `count` of the item if there are that many and returns whether it
+    // did. Emptied slots are closed up so the rest keep their order.
+    pub fn remove(&mut self, item: T, count: u8) -> bool {
+        if self.count(item) < count as u32 {
+            return false;
+        }
+
+        let mut left = count;
+        for (held, stack) in self.slots.iter_mut().rev().flatten() {
+            if *held == item {
+                let moved = left.min(*stack);
+                *stack -= moved;
+                left -= moved;
+            }
+        }
+
+        let mut kept = self
+            .slots
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&(_, stack)| stack > 0);
+        self.slots = core::array::from_fn(|_| kept.next());
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Bag = Inventory<char, 3>;
+
+    #[test]
+    fn items_stack_before_taking_new_slots() {
+        let mut bag = Bag::default();
+
+        assert!(bag.add('a', 2));
+        assert!(bag.add('b', 1));
+        assert!(bag.add('a', 3));
+
+        assert_eq!(bag.slots(), &[Some(('a', 5)), Some(('b', 1)), None]);
+        assert_eq!(bag.count('a'), 5);
+        assert_eq!(bag.count('c'), 0);
+    }
+
+    #[test]
+    fn full_stacks_spill_into_empty_slots() {
+        let mut bag = Bag::default();
+
+        assert!(bag.add('a', 98));
+        assert!(bag.add('a', 5));
+
+        assert_eq!(bag.slots(), &[Some(('a', 99)), Some(('a', 4)), None]);
+        assert_eq!(bag.count('a'), 103);
+    }
+
+    #[test]
+    fn nothing_is_added_when_it_doesnt_all_fit() {
+        let mut bag = Bag::default();
+        assert!(bag.add('a', 1));
+        assert!(bag.add('b', 1));
+        assert!(bag.add('c', 98));
+
+        assert!(!bag.add('d', 1));
+        assert!(!bag.add('c', 2));
+        assert!(bag.add('c', 1));
+
+        assert_eq!(
+            bag.slots(),
+            &[Some(('a', 1)), Some(('b', 1)), Some(('c', 99))]
+        );
+    }
+
+    #[test]
+    fn removing_closes_up_empty_slots() {
+        let mut bag = Bag::default();
+        bag.add('a', 1);
+        bag.add('b', 2);
+        bag.add('c', 1);
+
+        assert!(!bag.remove('b', 3));
+        assert!(bag.remove('b', 1));
+        assert_eq!(
+            bag.slots(),
+            &[Some(('a', 1)), Some(('b', 1)), Some(('c', 1))]
+        );
+
+        assert!(bag.remove('a', 1));
+        assert_eq!(bag.slots(), &[Some(('b', 1)), Some(('c', 1)), None]);
+    }
+}
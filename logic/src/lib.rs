@@ -0,0 +1,22 @@
+#![no_std]
+
+extern crate alloc;
+
+pub mod animation;
+pub mod collision;
+pub mod depth;
+pub mod input;
+pub mod inventory;
+pub mod map;
+pub mod menu;
+pub mod metatile;
+pub mod motion;
+pub mod movement;
+pub mod oam;
+pub mod rng;
+pub mod save;
+pub mod script;
+pub mod state;
+pub mod terrain;
+pub mod text;
+pub mod trigger;
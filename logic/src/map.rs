@@ -0,0 +1,75 @@
+use agb_fixnum::{Rect, Vector2D};
+
+use crate::terrain::attributes;
+
+pub const TILE_SIZE: i32 = 8;
+
+pub fn tile_at<const W: usize, const H: usize>(
+    tilemap: &[[usize; W]; H],
+    (x, y): (i32, i32),
+) -> Option<usize> {
+    if x < 0 || y < 0 {
+        return None;
+    }
+
+    tilemap.get(y as usize)?.get(x as usize).copied()
+}
+
+pub fn tile_of(point: Vector2D<i32>) -> (i32, i32) {
+    (point.x.div_euclid(TILE_SIZE), point.y.div_euclid(TILE_SIZE))
+}
+
+pub fn area_walkable<const W: usize, const H: usize>(
+    area: Rect<i32>,
+    tilemap: &[[usize; W]; H],
+) -> bool {
+    let (left, top) = tile_of(area.position);
+    let (right, bottom) = tile_of(area.position + area.size - (1, 1).into());
+
+    (top..=bottom).all(|y| {
+        (left..=right)
+            .all(|x| tile_at(tilemap, (x, y)).is_some_and(|tile| attributes(tile).walkable()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: [[usize; 4]; 3] = [[0, 0, 0, 0], [0, 5, 5, 0], [0, 5, 5, 0]];
+
+    fn area(x: i32, y: i32, w: i32, h: i32) -> Rect<i32> {
+        Rect::new((x, y).into(), (w, h).into())
+    }
+
+    #[test]
+    fn tile_at_is_none_outside_the_map() {
+        assert_eq!(tile_at(&MAP, (1, 1)), Some(5));
+        assert_eq!(tile_at(&MAP, (-1, 0)), None);
+        assert_eq!(tile_at(&MAP, (0, -1)), None);
+        assert_eq!(tile_at(&MAP, (4, 0)), None);
+        assert_eq!(tile_at(&MAP, (0, 3)), None);
+    }
+
+    #[test]
+    fn tile_of_rounds_negative_points_down() {
+        assert_eq!(tile_of((0, 7).into()), (0, 0));
+        assert_eq!(tile_of((8, 15).into()), (1, 1));
+        assert_eq!(tile_of((-1, -8).into()), (-1, -1));
+        assert_eq!(tile_of((-9, 0).into()), (-2, 0));
+    }
+
+    #[test]
+    fn area_walkable_needs_every_covered_tile_walkable() {
+        assert!(area_walkable(area(8, 8, 16, 16), &MAP));
+        assert!(area_walkable(area(10, 12, 4, 4), &MAP));
+        assert!(!area_walkable(area(7, 8, 16, 16), &MAP));
+        assert!(!area_walkable(area(8, 8, 17, 16), &MAP));
+    }
+
+    #[test]
+    fn area_walkable_off_the_map_is_blocked() {
+        assert!(!area_walkable(area(-8, 8, 16, 16), &MAP));
+        assert!(!area_walkable(area(8, 20, 16, 16), &MAP));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::input::{button, Action, Input};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuEvent {
+    Moved(usize),
+    Chosen(usize),
+    Closed,
+}
+
+// What a menu event should sound like.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sound {
+    Move,
+    Choose,
+    Close,
+}
+
+impl MenuEvent {
+    pub fn sound(self) -> Sound {
+        match self {
+            MenuEvent::Moved(_) => Sound::Move,
+            MenuEvent::Chosen(_) => Sound::Choose,
+            MenuEvent::Closed => Sound::Close,
+        }
+    }
+}
+
+// A cursor over a column of entries, moved with up and down and wrapping round
+// at either end. Confirm chooses the entry, Cancel or Menu closes it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cursor {
+    entries: usize,
+    selected: usize,
+}
+
+impl Cursor {
+    pub fn new(entries: usize) -> Self {
+        assert!(entries > 0, "a menu needs something in it");
+
+        Self {
+            entries,
+            selected: 0,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn tick(&mut self, input: &mut Input) -> Option<MenuEvent> {
+        if input.take(Action::Cancel) || input.take(Action::Menu) {
+            return Some(MenuEvent::Closed);
+        }
+
+        if input.take(Action::Confirm) {
+            return Some(MenuEvent::Chosen(self.selected));
+        }
+
+        let buttons = input.buttons();
+        let step = if buttons.is_just_pressed(button::DOWN) {
+            1
+        } else if buttons.is_just_pressed(button::UP) {
+            self.entries - 1
+        } else {
+            return None;
+        };
+
+        self.selected = (self.selected + step) % self.entries;
+        Some(MenuEvent::Moved(self.selected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Config;
+
+    fn press(cursor: &mut Cursor, input: &mut Input, buttons: u16) -> Option<MenuEvent> {
+        input.update(buttons);
+        let event = cursor.tick(input);
+        input.update(0);
+
+        event
+    }
+
+    #[test]
+    fn the_cursor_wraps_round() {
+        let mut input = Input::new(Config::default());
+        let mut cursor = Cursor::new(3);
+
+        assert_eq!(
+            press(&mut cursor, &mut input, button::UP),
+            Some(MenuEvent::Moved(2))
+        );
+        assert_eq!(
+            press(&mut cursor, &mut input, button::DOWN),
+            Some(MenuEvent::Moved(0))
+        );
+        assert_eq!(
+            press(&mut cursor, &mut input, button::DOWN),
+            Some(MenuEvent::Moved(1))
+        );
+        assert_eq!(cursor.selected(), 1);
+    }
+
+    #[test]
+    fn holding_a_direction_moves_once() {
+        let mut input = Input::new(Config::default());
+        let mut cursor = Cursor::new(3);
+
+        input.update(button::DOWN);
+        assert_eq!(cursor.tick(&mut input), Some(MenuEvent::Moved(1)));
+        input.update(button::DOWN);
+        assert_eq!(cursor.tick(&mut input), None);
+        assert_eq!(cursor.selected(), 1);
+    }
+
+    #[test]
+    fn confirm_chooses_and_cancel_or_menu_closes() {
+        let mut input = Input::new(Config::default());
+        let mut cursor = Cursor::new(2);
+
+        press(&mut cursor, &mut input, button::DOWN);
+        let chosen = press(&mut cursor, &mut input, button::A);
+        assert_eq!(chosen, Some(MenuEvent::Chosen(1)));
+        assert_eq!(chosen.map(MenuEvent::sound), Some(Sound::Choose));
+
+        assert_eq!(
+            press(&mut cursor, &mut input, button::B),
+            Some(MenuEvent::Closed)
+        );
+        assert_eq!(
+            press(&mut cursor, &mut input, button::START),
+            Some(MenuEvent::Closed)
+        );
+    }
+}
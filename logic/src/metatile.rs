@@ -0,0 +1,120 @@
+// A square of `size` by `size` map tiles that is placed as one, in reading
+// order.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Metatile {
+    pub tiles: &'static [usize],
+}
+
+impl Metatile {
+    pub const fn new(tiles: &'static [usize]) -> Self {
+        Self { tiles }
+    }
+}
+
+// A map made by mapgen's generate_metatile_map, `layout` holds an index into
+// `metatiles` for each metatile with `width` of them to a row.
+#[derive(Clone, Copy, Debug)]
+pub struct MetatileMap {
+    pub size: usize,
+    pub width: usize,
+    pub metatiles: &'static [Metatile],
+    pub layout: &'static [u8],
+}
+
+impl MetatileMap {
+    pub fn height(&self) -> usize {
+        self.layout.len() / self.width
+    }
+
+    fn metatile_at(&self, (x, y): (i32, i32)) -> Option<&'static Metatile> {
+        if x < 0 || y < 0 || x as usize >= self.width {
+            return None;
+        }
+
+        let index = *self.layout.get(y as usize * self.width + x as usize)?;
+        self.metatiles.get(index as usize)
+    }
+
+    // The map tile at `(x, y)`, counted in map tiles rather than metatiles. The
+    // game expands the whole map once when it's loaded, collision and terrain
+    // then go by plain tiles the same as for any other map.
+    pub fn tile_at(&self, (x, y): (i32, i32)) -> Option<usize> {
+        let size = self.size as i32;
+        let metatile = self.metatile_at((x.div_euclid(size), y.div_euclid(size)))?;
+
+        Some(metatile.tiles[(y.rem_euclid(size) * size + x.rem_euclid(size)) as usize])
+    }
+
+    // Plain map tiles for drawing and for the tile based collision, anything
+    // the layout doesn't reach is tile 0.
+    pub fn expand<const W: usize, const H: usize>(&self) -> [[usize; W]; H] {
+        let mut tilemap = [[0; W]; H];
+
+        for (y, row) in tilemap.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                *tile = self.tile_at((x as i32, y as i32)).unwrap_or(0);
+            }
+        }
+
+        tilemap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOOR: Metatile = Metatile::new(&[5, 5, 5, 5]);
+    const WALL: Metatile = Metatile::new(&[1, 2, 5, 5]);
+
+    // 3x2 metatiles, a wall along the top and floor below.
+    const MAP: MetatileMap = MetatileMap {
+        size: 2,
+        width: 3,
+        metatiles: &[FLOOR, WALL],
+        layout: &[1, 1, 1, 0, 0, 0],
+    };
+
+    #[test]
+    fn tiles_are_looked_up_through_their_metatile() {
+        assert_eq!(MAP.height(), 2);
+        assert_eq!(MAP.tile_at((0, 0)), Some(1));
+        assert_eq!(MAP.tile_at((3, 0)), Some(2));
+        assert_eq!(MAP.tile_at((5, 1)), Some(5));
+        assert_eq!(MAP.tile_at((6, 0)), None);
+        assert_eq!(MAP.tile_at((0, 4)), None);
+        assert_eq!(MAP.tile_at((-1, 0)), None);
+    }
+
+    #[test]
+    fn metatiles_can_be_three_tiles_square() {
+        // A 3x3 room, the player's size, with the door in the middle of the
+        // bottom row.
+        const ROOM: MetatileMap = MetatileMap {
+            size: 3,
+            width: 2,
+            metatiles: &[
+                Metatile::new(&[1, 2, 2, 6, 5, 5, 6, 5, 5]),
+                Metatile::new(&[2, 2, 3, 5, 5, 7, 5, 13, 7]),
+            ],
+            layout: &[0, 1],
+        };
+
+        let tilemap: [[usize; 6]; 3] = ROOM.expand();
+
+        assert_eq!(tilemap[0], [1, 2, 2, 2, 2, 3]);
+        assert_eq!(tilemap[1], [6, 5, 5, 5, 5, 7]);
+        assert_eq!(tilemap[2], [6, 5, 5, 5, 13, 7]);
+        assert_eq!(ROOM.tile_at((4, 2)), Some(13));
+        assert_eq!(ROOM.tile_at((6, 0)), None);
+    }
+
+    #[test]
+    fn expanding_fills_past_the_layout_with_zero() {
+        let tilemap: [[usize; 7]; 5] = MAP.expand();
+
+        assert_eq!(tilemap[0], [1, 2, 1, 2, 1, 2, 0]);
+        assert_eq!(tilemap[3], [5, 5, 5, 5, 5, 5, 0]);
+        assert_eq!(tilemap[4], [0; 7]);
+    }
+}
@@ -0,0 +1,402 @@
+use core::cell::Cell;
+
+use agb_fixnum::{Num, Rect, Vector2D};
+
+use crate::{
+    collision::{body, center, ledge_ahead, path_down_blocked, step_blocked},
+    map::TILE_SIZE,
+    terrain::{terrain_at, Rules, Terrain},
+};
+
+pub type Fixed = Num<i32, 8>;
+
+// Pixels per frame, and how much the speed changes each frame on the way up to
+// it and back down to a stop.
+pub const WALK_SPEED: Fixed = Fixed::from_raw(1 << 8);
+pub const RUN_SPEED: Fixed = Fixed::from_raw(2 << 8);
+pub const ACCELERATION: Fixed = Fixed::from_raw(1 << 6);
+pub const DECELERATION: Fixed = Fixed::from_raw(1 << 7);
+
+// A hop down a ledge carries the body clear of it, a fixed number of pixels a
+// frame, rising to HOP_HEIGHT halfway. It lands a pixel past the grid, where
+// walking up stops, so the ledge can't be walked back up.
+pub const HOP_DISTANCE: i32 = 3 * TILE_SIZE + 1;
+pub const HOP_SPEED: i32 = 2;
+pub const HOP_HEIGHT: i32 = 8;
+
+// Free movement for the player in app.rs, with the position kept in fractions
+// of a pixel so speeding up and slowing down are smooth.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Motion {
+    pub position: Vector2D<Fixed>,
+    pub velocity: Vector2D<Fixed>,
+    // Pixels of a ledge hop still to go.
+    hop: Option<i32>,
+    on_ice: bool,
+}
+
+impl Motion {
+    pub fn new((x, y): (i16, i16)) -> Self {
+        Self {
+            position: Vector2D::new(Fixed::new(x as i32), Fixed::new(y as i32)),
+            velocity: Vector2D::default(),
+            hop: None,
+            on_ice: false,
+        }
+    }
+
+    // The pixel the sprite is drawn at.
+    pub fn pixel(&self) -> (i16, i16) {
+        (
+            self.position.x.floor() as i16,
+            self.position.y.floor() as i16,
+        )
+    }
+
+    // How far above the ground the sprite is drawn, during a hop.
+    pub fn height(&self) -> i32 {
+        let Some(left) = self.hop else {
+            return 0;
+        };
+        let done = HOP_DISTANCE - left;
+
+        let area = HOP_DISTANCE * HOP_DISTANCE;
+        (4 * HOP_HEIGHT * done * left + area / 2) / area
+    }
+
+    // Speeds up in the direction pushed at walking or running speed, and slows
+    // down on any axis that isn't being pushed. Nothing can be done mid-hop, and
+    // on ice the player slides at walking speed or more and can only push off
+    // from standing still.
+    pub fn steer(&mut self, (x, y): (i32, i32), running: bool) {
+        let speed = if running { RUN_SPEED } else { WALK_SPEED };
+
+        if self.hop.is_some() {
+            return;
+        }
+
+        if self.on_ice {
+            self.velocity = if self.velocity == Vector2D::default() {
+                Vector2D::new(speed * x.signum(), speed * y.signum())
+            } else {
+                Vector2D::new(slide(self.velocity.x), slide(self.velocity.y))
+            };
+            return;
+        }
+
+        self.velocity.x = approach(self.velocity.x, speed * x.signum());
+        self.velocity.y = approach(self.velocity.y, speed * y.signum());
+    }
+
+    // Applies a frame of velocity. Whole pixels are crossed one at a time so
+    // nothing gets skipped at speed, hitting something stops that axis dead.
+    // Walking down onto a ledge stops at its edge and starts a hop if there's
+    // room to land.
+    pub fn advance<const W: usize, const H: usize>(
+        &mut self,
+        tilemap: &[[usize; W]; H],
+        rules: Rules,
+        occupied: impl Fn(Rect<i32>) -> bool,
+    ) {
+        if let Some(left) = self.hop {
+            self.advance_hop(left, tilemap, rules, &occupied);
+            return;
+        }
+
+        let (x, y) = self.pixel();
+
+        let target = self.position.x + self.velocity.x;
+        let x = cross(x, target.floor(), |x, dx| {
+            step_blocked((x, y), (dx, 0), tilemap, rules, &occupied)
+        });
+        (self.position.x, self.velocity.x) = settle(x, target, self.velocity.x);
+
+        let ledge = Cell::new(false);
+        let target = self.position.y + self.velocity.y;
+        let y = cross(y, target.floor(), |y, dy| {
+            ledge.set(dy > 0 && ledge_ahead((x, y), tilemap));
+            ledge.get() || step_blocked((x, y), (0, dy), tilemap, rules, &occupied)
+        });
+        (self.position.y, self.velocity.y) = settle(y, target, self.velocity.y);
+
+        if ledge.get() {
+            self.hop_from((x, y), tilemap, rules, &occupied);
+        }
+
+        self.on_ice =
+            terrain_at(tilemap, center(body(Vector2D::new(x as i32, y as i32)))) == Terrain::Ice;
+    }
+
+    fn hop_from<const W: usize, const H: usize>(
+        &mut self,
+        (x, y): (i16, i16),
+        tilemap: &[[usize; W]; H],
+        rules: Rules,
+        occupied: &impl Fn(Rect<i32>) -> bool,
+    ) {
+        let landing = (1..=HOP_DISTANCE / TILE_SIZE)
+            .all(|row| !path_down_blocked((x, y + (row * TILE_SIZE) as i16), tilemap, rules))
+            && !occupied(body(Vector2D::new(x as i32, y as i32 + HOP_DISTANCE)));
+
+        if landing {
+            self.hop = Some(HOP_DISTANCE);
+            self.velocity = Vector2D::default();
+        }
+    }
+
+    fn advance_hop<const W: usize, const H: usize>(
+        &mut self,
+        left: i32,
+        tilemap: &[[usize; W]; H],
+        rules: Rules,
+        occupied: &impl Fn(Rect<i32>) -> bool,
+    ) {
+        let (x, y) = self.pixel();
+        let target = y as i32 + HOP_SPEED.min(left);
+
+        let reached = cross(y, target, |y, dy| {
+            step_blocked((x, y), (0, dy), tilemap, rules, occupied)
+        });
+        self.position.y = Fixed::new(reached as i32);
+
+        let left = left - (reached - y) as i32;
+        self.hop = Some(left).filter(|&left| left > 0 && reached as i32 == target);
+    }
+}
+
+// Moves a pixel at a time from `from` towards `to` until `blocked` says the
+// next pixel in that direction is off limits.
+fn cross(mut from: i16, to: i32, blocked: impl Fn(i16, i16) -> bool) -> i16 {
+    let step = (to - from as i32).signum() as i16;
+
+    while from as i32 != to && !blocked(from, step) {
+        from += step;
+    }
+
+    from
+}
+
+// Where an axis ends up after crossing: at the target if it got there, or
+// stopped dead on the pixel it reached.
+fn settle(reached: i16, target: Fixed, velocity: Fixed) -> (Fixed, Fixed) {
+    if reached as i32 == target.floor() {
+        (target, velocity)
+    } else {
+        (Fixed::new(reached as i32), Fixed::new(0))
+    }
+}
+
+fn slide(velocity: Fixed) -> Fixed {
+    if velocity == Fixed::new(0) || velocity.abs() >= WALK_SPEED {
+        velocity
+    } else if velocity > Fixed::new(0) {
+        WALK_SPEED
+    } else {
+        -WALK_SPEED
+    }
+}
+
+fn approach(velocity: Fixed, target: Fixed) -> Fixed {
+    let speeding_up = target != Fixed::new(0)
+        && (velocity == Fixed::new(0) || (velocity > Fixed::new(0)) == (target > Fixed::new(0)))
+        && velocity.abs() < target.abs();
+    let rate = if speeding_up {
+        ACCELERATION
+    } else {
+        DECELERATION
+    };
+
+    if velocity < target {
+        (velocity + rate).min(target)
+    } else {
+        (velocity - rate).max(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Grass everywhere with a wall along x = 64.
+    fn field() -> [[usize; 16]; 16] {
+        let mut tilemap = [[5; 16]; 16];
+        for row in tilemap.iter_mut() {
+            row[8] = 0;
+        }
+        tilemap
+    }
+
+    #[test]
+    fn speeds_up_to_walking_and_running() {
+        let mut motion = Motion::new((0, 0));
+
+        for _ in 0..3 {
+            motion.steer((1, 0), false);
+        }
+        assert_eq!(motion.velocity.x, Fixed::from_raw(3 << 6));
+
+        for _ in 0..10 {
+            motion.steer((1, 0), false);
+        }
+        assert_eq!(motion.velocity.x, WALK_SPEED);
+
+        for _ in 0..10 {
+            motion.steer((1, -1), true);
+        }
+        assert_eq!(motion.velocity, Vector2D::new(RUN_SPEED, -RUN_SPEED));
+    }
+
+    #[test]
+    fn slows_down_when_let_go_or_turned_around() {
+        let mut motion = Motion::new((0, 0));
+        motion.velocity.x = RUN_SPEED;
+
+        motion.steer((0, 0), false);
+        assert_eq!(motion.velocity.x, RUN_SPEED - DECELERATION);
+
+        motion.steer((1, 0), false);
+        assert_eq!(motion.velocity.x, RUN_SPEED - DECELERATION * 2);
+
+        motion.velocity.x = WALK_SPEED;
+        motion.steer((-1, 0), false);
+        assert_eq!(motion.velocity.x, WALK_SPEED - DECELERATION);
+    }
+
+    #[test]
+    fn moves_by_fractions_of_a_pixel() {
+        let tilemap = field();
+        let mut motion = Motion::new((8, 8));
+        motion.velocity.x = Fixed::from_raw(1 << 7);
+
+        motion.advance(&tilemap, Rules::default(), |_| false);
+        assert_eq!(motion.pixel(), (8, 8));
+        motion.advance(&tilemap, Rules::default(), |_| false);
+        assert_eq!(motion.pixel(), (9, 8));
+    }
+
+    #[test]
+    fn fast_movement_doesnt_skip_walls() {
+        let tilemap = field();
+
+        // The body spans tile columns x / 8 + 1 to x / 8 + 3, so the wall in
+        // column 8 stops the sprite at x = 39.
+        for speed in [WALK_SPEED, RUN_SPEED, Fixed::new(3), Fixed::new(7)] {
+            let mut motion = Motion::new((1, 8));
+
+            for _ in 0..40 {
+                motion.velocity.x = speed;
+                motion.advance(&tilemap, Rules::default(), |_| false);
+            }
+
+            assert_eq!(motion.pixel(), (39, 8));
+            assert_eq!(motion.velocity.x, Fixed::new(0));
+        }
+    }
+
+    // A ledge along row 6 with grass above and below it, and ice from row 10 down
+    // on the right half.
+    fn slopes() -> [[usize; 16]; 16] {
+        let mut tilemap = [[5; 16]; 16];
+        tilemap[6] = [11; 16];
+        for row in tilemap.iter_mut().skip(10) {
+            row[8..].fill(12);
+        }
+        tilemap
+    }
+
+    #[test]
+    fn ledges_are_hopped_down_but_not_climbed() {
+        let tilemap = slopes();
+        let mut motion = Motion::new((0, 0));
+        let mut highest = 0;
+
+        for _ in 0..60 {
+            motion.steer((0, 1), false);
+            motion.advance(&tilemap, Rules::default(), |_| false);
+            highest = highest.max(motion.height());
+        }
+
+        // Stopped at the edge, y = 24, then carried over to 49 and kept going.
+        assert!(motion.pixel().1 > 49);
+        assert_eq!(highest, HOP_HEIGHT);
+
+        for _ in 0..60 {
+            motion.steer((0, -1), false);
+            motion.advance(&tilemap, Rules::default(), |_| false);
+        }
+        assert_eq!(motion.pixel(), (0, 49));
+    }
+
+    #[test]
+    fn input_is_ignored_mid_hop() {
+        let tilemap = slopes();
+        let mut motion = Motion::new((0, 24));
+
+        while motion.hop.is_none() {
+            motion.steer((0, 1), false);
+            motion.advance(&tilemap, Rules::default(), |_| false);
+        }
+        for _ in 0..5 {
+            motion.steer((1, -1), true);
+            motion.advance(&tilemap, Rules::default(), |_| false);
+        }
+
+        assert_eq!(motion.pixel(), (0, 24 + 5 * HOP_SPEED as i16));
+    }
+
+    #[test]
+    fn a_blocked_landing_means_no_hop() {
+        let tilemap = slopes();
+        let mut motion = Motion::new((0, 24));
+        let npc = Rect::new((0, 70).into(), (16, 16).into());
+
+        for _ in 0..20 {
+            motion.steer((0, 1), false);
+            motion.advance(&tilemap, Rules::default(), |area| area.touches(npc));
+        }
+
+        assert_eq!(motion.pixel(), (0, 24));
+    }
+
+    #[test]
+    fn ice_slides_until_something_is_hit() {
+        let tilemap = slopes();
+        // The centre of the body is on the ice.
+        let mut motion = Motion::new((64, 80));
+
+        motion.steer((0, 1), false);
+        motion.advance(&tilemap, Rules::default(), |_| false);
+        for _ in 0..10 {
+            motion.steer((-1, -1), true);
+            motion.advance(&tilemap, Rules::default(), |_| false);
+        }
+        assert_eq!(motion.pixel(), (64, 90));
+
+        // Off the bottom of the map it stops, then can push off again.
+        for _ in 0..100 {
+            motion.steer((0, 0), false);
+            motion.advance(&tilemap, Rules::default(), |_| false);
+        }
+        assert_eq!(motion.pixel(), (64, 103));
+
+        motion.steer((1, 0), false);
+        for _ in 0..10 {
+            motion.advance(&tilemap, Rules::default(), |_| false);
+        }
+        assert_eq!(motion.pixel(), (74, 103));
+    }
+
+    #[test]
+    fn occupied_areas_block_too() {
+        let tilemap = field();
+        let npc = Rect::new((8, 40).into(), (16, 16).into());
+        let mut motion = Motion::new((0, 0));
+        motion.velocity.y = RUN_SPEED;
+
+        for _ in 0..20 {
+            motion.advance(&tilemap, Rules::default(), |area| area.touches(npc));
+        }
+
+        assert_eq!(motion.pixel(), (0, 8));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{
+    motion::HOP_HEIGHT,
+    terrain::{Attributes, Rules, Terrain},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MovementType {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+// Frames between reads of the d-pad in app_bc.
+pub const INPUT_PERIOD: u32 = 5;
+
+// A grid step in progress, moves one pixel per tick until `step` pixels are done.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Movement {
+    pub tp: MovementType,
+    pub step: i32,
+}
+
+impl Movement {
+    pub fn new(tp: MovementType, step: i32) -> Self {
+        Self { tp, step }
+    }
+
+    // Y wins when both axes are held, as it always has in app_bc.
+    pub fn from_axes((x, y): (i32, i32), step: i32) -> Option<Self> {
+        let tp = match (x.signum(), y.signum()) {
+            (_, 1) => MovementType::Down,
+            (_, -1) => MovementType::Up,
+            (1, _) => MovementType::Right,
+            (-1, _) => MovementType::Left,
+            _ => return None,
+        };
+
+        Some(Self::new(tp, step))
+    }
+
+    pub fn delta(&self) -> (i32, i32) {
+        match self.tp {
+            MovementType::Up => (0, -1),
+            MovementType::Down => (0, 1),
+            MovementType::Left => (-1, 0),
+            MovementType::Right => (1, 0),
+        }
+    }
+
+    // Whatever is left after moving this tick, None once the step is done.
+    pub fn next(self) -> Option<Self> {
+        if self.step <= 1 {
+            return None;
+        }
+
+        Some(Self::new(self.tp, self.step - 1))
+    }
+}
+
+// The player in app_bc. A step that's under way moves a pixel every frame, and
+// every INPUT_PERIOD frames the d-pad is read and it gets an extra tick. Steps
+// down onto a ledge hop over it as a double step, and steps ending on ice carry
+// on the same way until something is in the way.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GridWalker {
+    pub position: (i32, i32),
+    pub movement: Option<Movement>,
+    step: i32,
+    count: u32,
+    hop: bool,
+}
+
+impl GridWalker {
+    pub fn new(position: (i32, i32), step: i32) -> Self {
+        Self {
+            position,
+            movement: None,
+            step,
+            count: 0,
+            hop: false,
+        }
+    }
+
+    // How far above the ground the sprite is drawn, during a hop.
+    pub fn height(&self) -> i32 {
+        match self.movement {
+            Some(movement) if self.hop => {
+                let total = 2 * self.step;
+                let done = total - movement.step;
+                (4 * HOP_HEIGHT * done * movement.step + total * total / 2) / (total * total)
+            }
+            _ => 0,
+        }
+    }
+
+    // Returns which way the walker moved this frame, if it did. `ground` gives
+    // the attributes of the ground at a walker position.
+    pub fn frame(
+        &mut self,
+        axes: (i32, i32),
+        rules: Rules,
+        ground: impl Fn((i32, i32)) -> Attributes,
+    ) -> Option<MovementType> {
+        let mut moved = self.tick(rules, &ground);
+
+        if self.count.is_multiple_of(INPUT_PERIOD) {
+            if self.movement.is_none() {
+                self.movement = Movement::from_axes(axes, self.step)
+                    .and_then(|movement| self.start(movement.tp, rules, &ground));
+            }
+
+            moved = self.tick(rules, &ground).or(moved);
+            self.count = 0;
+        }
+
+        self.count += 1;
+        moved
+    }
+
+    // The step to take towards `tp`, if the ground allows it.
+    fn start(
+        &mut self,
+        tp: MovementType,
+        rules: Rules,
+        ground: &impl Fn((i32, i32)) -> Attributes,
+    ) -> Option<Movement> {
+        let (dx, dy) = Movement::new(tp, self.step).delta();
+        let ahead = |steps: i32| {
+            (
+                self.position.0 + dx * self.step * steps,
+                self.position.1 + dy * self.step * steps,
+            )
+        };
+
+        let next = ground(ahead(1));
+        if !next.enterable((dx, dy), rules) {
+            return None;
+        }
+
+        self.hop = next.terrain == Terrain::Ledge;
+        if !self.hop {
+            return Some(Movement::new(tp, self.step));
+        }
+
+        let landing = ground(ahead(2));
+        if landing.terrain == Terrain::Ledge || !landing.enterable((dx, dy), rules) {
+            self.hop = false;
+            return None;
+        }
+
+        Some(Movement::new(tp, self.step * 2))
+    }
+
+    fn tick(
+        &mut self,
+        rules: Rules,
+        ground: &impl Fn((i32, i32)) -> Attributes,
+    ) -> Option<MovementType> {
+        let movement = self.movement?;
+        let (dx, dy) = movement.delta();
+
+        self.position = (self.position.0 + dx, self.position.1 + dy);
+        self.movement = movement.next();
+
+        if self.movement.is_none() {
+            self.hop = false;
+
+            if ground(self.position).terrain == Terrain::Ice {
+                self.movement = self.start(movement.tp, rules, ground);
+            }
+        }
+
+        Some(movement.tp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::attributes;
+
+    fn travel(movement: Movement) -> ((i32, i32), usize) {
+        let mut position = (0, 0);
+        let mut ticks = 0;
+        let mut movement = Some(movement);
+
+        while let Some(current) = movement {
+            let (dx, dy) = current.delta();
+            position = (position.0 + dx, position.1 + dy);
+            ticks += 1;
+            movement = current.next();
+        }
+
+        (position, ticks)
+    }
+
+    #[test]
+    fn a_step_moves_exactly_step_pixels() {
+        assert_eq!(
+            travel(Movement::new(MovementType::Right, 32)),
+            ((32, 0), 32)
+        );
+        assert_eq!(
+            travel(Movement::new(MovementType::Left, 32)),
+            ((-32, 0), 32)
+        );
+        assert_eq!(travel(Movement::new(MovementType::Down, 24)), ((0, 24), 24));
+        assert_eq!(travel(Movement::new(MovementType::Up, 8)), ((0, -8), 8));
+    }
+
+    #[test]
+    fn single_pixel_step_finishes_straight_away() {
+        assert_eq!(Movement::new(MovementType::Up, 1).next(), None);
+        assert_eq!(travel(Movement::new(MovementType::Up, 1)), ((0, -1), 1));
+    }
+
+    #[test]
+    fn y_overrides_x() {
+        assert_eq!(
+            Movement::from_axes((1, -1), 8),
+            Some(Movement::new(MovementType::Up, 8))
+        );
+        assert_eq!(
+            Movement::from_axes((-1, 0), 8),
+            Some(Movement::new(MovementType::Left, 8))
+        );
+        assert_eq!(Movement::from_axes((0, 0), 8), None);
+    }
+
+    // Ground one step of 4 pixels to a cell: a ledge along row 2, ice in
+    // columns 2 and 3 of row 4 with a wall after it, grass everywhere else.
+    fn ground((x, y): (i32, i32)) -> Attributes {
+        let tile = match (x.div_euclid(4), y.div_euclid(4)) {
+            (_, 2) => 11,
+            (2 | 3, 4) => 12,
+            (4, 4) => 0,
+            (0..8, 0..8) => 5,
+            _ => 0,
+        };
+
+        attributes(tile)
+    }
+
+    fn walk(walker: &mut GridWalker, axes: (i32, i32), frames: u32) {
+        for _ in 0..frames {
+            walker.frame(axes, Rules::default(), ground);
+        }
+    }
+
+    #[test]
+    fn walker_only_reads_input_every_period() {
+        let mut walker = GridWalker::new((0, 0), 4);
+
+        assert_eq!(
+            walker.frame((1, 0), Rules::default(), ground),
+            Some(MovementType::Right)
+        );
+        assert_eq!(walker.position, (1, 0));
+
+        walk(&mut walker, (0, 1), INPUT_PERIOD - 1);
+        assert_eq!(walker.position, (4, 0));
+        assert_eq!(walker.movement, None);
+
+        assert_eq!(
+            walker.frame((0, 1), Rules::default(), ground),
+            Some(MovementType::Down)
+        );
+        assert_eq!(walker.position, (4, 1));
+    }
+
+    #[test]
+    fn walker_hops_down_ledges_and_cant_climb_them() {
+        let mut walker = GridWalker::new((0, 4), 4);
+
+        walk(&mut walker, (0, 1), 1);
+        assert_eq!(walker.movement, Some(Movement::new(MovementType::Down, 7)));
+
+        let mut highest = 0;
+        for _ in 0..7 {
+            walk(&mut walker, (0, 0), 1);
+            highest = highest.max(walker.height());
+        }
+        assert_eq!(walker.position, (0, 12));
+        assert_eq!(highest, HOP_HEIGHT);
+
+        walk(&mut walker, (0, -1), 30);
+        assert_eq!(walker.position, (0, 12));
+    }
+
+    #[test]
+    fn walker_slides_across_ice_until_blocked() {
+        let mut walker = GridWalker::new((4, 16), 4);
+
+        walk(&mut walker, (1, 0), 1);
+        walk(&mut walker, (0, 0), 30);
+        assert_eq!(walker.position, (12, 16));
+
+        walk(&mut walker, (0, -1), 10);
+        assert_eq!(walker.position, (12, 12));
+    }
+}
@@ -0,0 +1,143 @@
+// Hardware limits on sprites: OAM entries, and 4bpp tiles of sprite VRAM in the
+// tiled background modes.
+pub const OBJECTS: usize = 128;
+pub const SPRITE_TILES: usize = 1024;
+
+// Hands out OAM slots for a frame. When more sprites want to be on screen than
+// there are slots, a different set misses out each frame so everything flickers
+// instead of the same sprites vanishing for good.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Slots {
+    capacity: usize,
+    reserved: usize,
+    wanted: usize,
+    granted: usize,
+    last_wanted: usize,
+    offset: usize,
+}
+
+impl Slots {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            reserved: 0,
+            wanted: 0,
+            granted: 0,
+            last_wanted: 0,
+            offset: 0,
+        }
+    }
+
+    // What's left for this frame once anything reserved is taken out.
+    pub fn capacity(&self) -> usize {
+        self.capacity.saturating_sub(self.reserved)
+    }
+
+    // Holds back `count` slots for this frame only, for things like menus that
+    // are only on screen some of the time. Call before any requests.
+    pub fn reserve(&mut self, count: usize) {
+        self.reserved += count;
+    }
+
+    // Sprites ask in the same order every frame, that's what makes the turns fair.
+    pub fn request(&mut self) -> bool {
+        let capacity = self.capacity();
+        let index = self.wanted;
+        self.wanted += 1;
+
+        let turn = if self.last_wanted > capacity {
+            (index + self.last_wanted - self.offset) % self.last_wanted
+        } else {
+            index
+        };
+
+        let granted = turn < capacity && self.granted < capacity;
+        if granted {
+            self.granted += 1;
+        }
+
+        granted
+    }
+
+    // Ends the frame, returning how many sprites asked for a slot.
+    pub fn finish(&mut self) -> usize {
+        let wanted = self.wanted;
+        let capacity = self.capacity();
+
+        self.offset = if wanted > capacity {
+            (self.offset + capacity) % wanted
+        } else {
+            0
+        };
+        self.last_wanted = wanted;
+        self.wanted = 0;
+        self.granted = 0;
+        self.reserved = 0;
+
+        wanted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(slots: &mut Slots, sprites: usize) -> [bool; 8] {
+        let mut shown = [false; 8];
+        for slot in shown.iter_mut().take(sprites) {
+            *slot = slots.request();
+        }
+        slots.finish();
+
+        shown
+    }
+
+    #[test]
+    fn everything_fits_under_capacity() {
+        let mut slots = Slots::new(4);
+
+        for _ in 0..3 {
+            assert_eq!(frame(&mut slots, 4)[..4], [true; 4]);
+        }
+    }
+
+    #[test]
+    fn over_capacity_takes_turns() {
+        let mut slots = Slots::new(3);
+        let mut seen = [0; 5];
+
+        for _ in 0..6 {
+            let shown = frame(&mut slots, 5);
+            assert_eq!(shown.iter().filter(|&&shown| shown).count(), 3);
+
+            for (count, shown) in seen.iter_mut().zip(shown) {
+                *count += shown as u32;
+            }
+        }
+
+        // 18 slots over 5 sprites, nobody goes without.
+        assert!(seen.iter().all(|&count| count >= 3));
+    }
+
+    #[test]
+    fn never_grants_more_than_capacity_when_demand_jumps() {
+        let mut slots = Slots::new(3);
+
+        frame(&mut slots, 4);
+        let shown = frame(&mut slots, 8);
+
+        assert_eq!(shown.iter().filter(|&&shown| shown).count(), 3);
+    }
+
+    #[test]
+    fn reserving_only_lasts_a_frame() {
+        let mut slots = Slots::new(4);
+
+        slots.reserve(3);
+        assert_eq!(slots.capacity(), 1);
+        assert_eq!(frame(&mut slots, 2)[..2], [true, false]);
+
+        assert_eq!(slots.capacity(), 4);
+        assert_eq!(frame(&mut slots, 2)[..2], [true, true]);
+    }
+}
@@ -0,0 +1,81 @@
+// A small xorshift generator, so the same seed always gives the same rolls and
+// replays stay deterministic.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    // Xorshift gets stuck on 0, so a zero seed is swapped for another.
+    pub const fn new(seed: u32) -> Self {
+        Self {
+            state: if seed == 0 { 0x9e37_79b9 } else { seed },
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    // A number in 0..n, n has to be above 0.
+    pub fn below(&mut self, n: u32) -> u32 {
+        ((self.next_u32() as u64 * n as u64) >> 32) as u32
+    }
+
+    // True `percent` times out of a hundred.
+    pub fn roll(&mut self, percent: u8) -> bool {
+        self.below(100) < percent as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_gives_the_same_numbers() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+        assert_ne!(Rng::new(1).next_u32(), Rng::new(2).next_u32());
+    }
+
+    #[test]
+    fn a_zero_seed_still_moves() {
+        let mut rng = Rng::new(0);
+
+        assert_ne!(rng.next_u32(), 0);
+        assert_ne!(rng.next_u32(), rng.next_u32());
+    }
+
+    #[test]
+    fn below_stays_in_range_and_covers_it() {
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 6];
+
+        for _ in 0..200 {
+            let n = rng.below(6);
+            assert!(n < 6);
+            seen[n as usize] = true;
+        }
+        assert_eq!(seen, [true; 6]);
+    }
+
+    #[test]
+    fn rolls_are_never_and_always_at_the_ends() {
+        let mut rng = Rng::new(3);
+
+        assert!((0..100).all(|_| !rng.roll(0)));
+        assert!((0..100).all(|_| rng.roll(100)));
+
+        let hits = (0..1000).filter(|_| rng.roll(25)).count();
+        assert!((200..300).contains(&hits), "{hits}");
+    }
+}
@@ -0,0 +1,345 @@
+// The layout of a save slot, and readers and writers for the parts of a save
+// that live in this crate. Where the slots are stored is up to the game.
+use crate::{
+    input::{Config, Diagonal},
+    inventory::{Inventory, MAX_STACK},
+    state::State,
+};
+
+const MAGIC: [u8; 4] = *b"SMAN";
+
+// magic, version, sequence, payload length, checksum
+pub const HEADER_SIZE: usize = 12;
+pub const SLOT_SIZE: usize = 256;
+pub const SLOTS: usize = 2;
+
+const EMPTY_SLOT: u8 = u8::MAX;
+
+// A slot that passed its checksum, with the payload still to be decoded by
+// whichever version wrote it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Slot<'a> {
+    pub version: u16,
+    pub sequence: u16,
+    pub payload: &'a [u8],
+}
+
+// Fills in the header in front of a payload already written after it, and
+// returns how much of the buffer to store.
+pub fn seal(buffer: &mut [u8], version: u16, sequence: u16, length: usize) -> usize {
+    buffer[0..4].copy_from_slice(&MAGIC);
+    buffer[4..6].copy_from_slice(&version.to_le_bytes());
+    buffer[6..8].copy_from_slice(&sequence.to_le_bytes());
+    buffer[8..10].copy_from_slice(&(length as u16).to_le_bytes());
+
+    let checksum = checksum(&buffer[4..10], &buffer[HEADER_SIZE..HEADER_SIZE + length]);
+    buffer[10..12].copy_from_slice(&checksum.to_le_bytes());
+
+    HEADER_SIZE + length
+}
+
+// None for a slot that was never written, was cut short or doesn't match its
+// checksum.
+pub fn open(buffer: &[u8]) -> Option<Slot<'_>> {
+    let mut header = Reader(buffer.get(..HEADER_SIZE)?);
+    if [header.u8()?, header.u8()?, header.u8()?, header.u8()?] != MAGIC {
+        return None;
+    }
+
+    let version = header.u16()?;
+    let sequence = header.u16()?;
+    let length = header.u16()? as usize;
+    let stored = header.u16()?;
+
+    let payload = buffer.get(HEADER_SIZE..HEADER_SIZE + length)?;
+    if checksum(&buffer[4..10], payload) != stored {
+        return None;
+    }
+
+    Some(Slot {
+        version,
+        sequence,
+        payload,
+    })
+}
+
+// Two slots are written alternately, so losing power half way through a save
+// only ever corrupts the older of the two. This picks the index of the newest
+// slot that could be read. Sequence numbers wrap, so the newer of two is the one
+// less than half the range ahead.
+pub fn newest(sequences: &[Option<u16>]) -> Option<usize> {
+    let mut newest: Option<(usize, u16)> = None;
+
+    for (slot, &sequence) in sequences.iter().enumerate() {
+        let Some(sequence) = sequence else {
+            continue;
+        };
+
+        let newer = match newest {
+            Some((_, current)) => sequence.wrapping_sub(current) as i16 > 0,
+            None => true,
+        };
+
+        if newer {
+            newest = Some((slot, sequence));
+        }
+    }
+
+    newest.map(|(slot, _)| slot)
+}
+
+// Fletcher-16 over the header fields and the payload.
+pub fn checksum(header: &[u8], payload: &[u8]) -> u16 {
+    let (mut low, mut high) = (0u16, 0u16);
+
+    for &byte in header.iter().chain(payload) {
+        low = (low + byte as u16) % 255;
+        high = (high + low) % 255;
+    }
+
+    (high << 8) | low
+}
+
+// Little endian fields off the front of a payload, None once it runs out.
+pub struct Reader<'a>(pub &'a [u8]);
+
+impl Reader<'_> {
+    pub fn u8(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.0.split_first()?;
+        self.0 = rest;
+
+        Some(byte)
+    }
+
+    pub fn i16(&mut self) -> Option<i16> {
+        Some(i16::from_le_bytes([self.u8()?, self.u8()?]))
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes([self.u8()?, self.u8()?]))
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        Some(self.u16()? as u32 | ((self.u16()? as u32) << 16))
+    }
+
+    pub fn config(&mut self) -> Option<Config> {
+        let mut config = Config::default();
+
+        for binding in config.bindings.iter_mut() {
+            *binding = self.u16()?;
+        }
+        config.diagonal = match self.u8()? {
+            0 => Diagonal::Both,
+            1 => Diagonal::Horizontal,
+            2 => Diagonal::Vertical,
+            3 => Diagonal::LastPressed,
+            _ => return None,
+        };
+
+        Some(config)
+    }
+
+    pub fn state(&mut self) -> Option<State> {
+        let mut state = State::default();
+
+        for word in state.flags.iter_mut() {
+            *word = self.u32()?;
+        }
+        for var in state.vars.iter_mut() {
+            *var = self.u8()?;
+        }
+
+        Some(state)
+    }
+
+    // Items are stored as their index in `items`.
+    pub fn inventory<T: Copy + Eq, const N: usize>(
+        &mut self,
+        items: &[T],
+    ) -> Option<Inventory<T, N>> {
+        let mut slots = [None; N];
+
+        for slot in slots.iter_mut() {
+            let (item, count) = (self.u8()?, self.u8()?);
+            if item != EMPTY_SLOT {
+                if count == 0 || count > MAX_STACK {
+                    return None;
+                }
+                *slot = Some((*items.get(item as usize)?, count));
+            }
+        }
+
+        Some(Inventory::from_slots(slots))
+    }
+}
+
+// Little endian fields into a buffer, which has to have room for them. The
+// second field is how much has been written.
+pub struct Writer<'a>(pub &'a mut [u8], pub usize);
+
+impl Writer<'_> {
+    pub fn u8(&mut self, value: u8) {
+        self.0[self.1] = value;
+        self.1 += 1;
+    }
+
+    pub fn i16(&mut self, value: i16) {
+        self.u16(value as u16);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        for byte in value.to_le_bytes() {
+            self.u8(byte);
+        }
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        for byte in value.to_le_bytes() {
+            self.u8(byte);
+        }
+    }
+
+    pub fn config(&mut self, config: &Config) {
+        for binding in config.bindings {
+            self.u16(binding);
+        }
+        self.u8(match config.diagonal {
+            Diagonal::Both => 0,
+            Diagonal::Horizontal => 1,
+            Diagonal::Vertical => 2,
+            Diagonal::LastPressed => 3,
+        });
+    }
+
+    pub fn state(&mut self, state: &State) {
+        for word in state.flags {
+            self.u32(word);
+        }
+        for var in state.vars {
+            self.u8(var);
+        }
+    }
+
+    // Every item held has to be in `items`.
+    pub fn inventory<T: Copy + Eq, const N: usize>(
+        &mut self,
+        inventory: &Inventory<T, N>,
+        items: &[T],
+    ) {
+        for slot in inventory.slots() {
+            match slot {
+                Some((item, count)) => {
+                    self.u8(items.iter().position(|held| held == item).unwrap() as u8);
+                    self.u8(*count);
+                }
+                None => {
+                    self.u8(EMPTY_SLOT);
+                    self.u8(0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Flag, Var};
+
+    const ITEMS: [char; 3] = ['a', 'b', 'c'];
+
+    // A whole save's worth of logic state, written after a header.
+    fn written(version: u16, sequence: u16) -> ([u8; SLOT_SIZE], usize) {
+        let config = Config {
+            diagonal: Diagonal::LastPressed,
+            ..Config::default()
+        };
+
+        let mut state = State::default();
+        state.set(Flag(37), true);
+        state.vars[Var(3).0 as usize] = 9;
+
+        let mut bag = Inventory::<char, 4>::default();
+        bag.add('c', 12);
+        bag.add('a', 1);
+
+        let mut buffer = [0; SLOT_SIZE];
+        let mut writer = Writer(&mut buffer[HEADER_SIZE..], 0);
+        writer.i16(-40);
+        writer.config(&config);
+        writer.state(&state);
+        writer.inventory(&bag, &ITEMS);
+        let length = writer.1;
+
+        let length = seal(&mut buffer, version, sequence, length);
+        (buffer, length)
+    }
+
+    #[test]
+    fn round_trips_through_a_slot() {
+        let (buffer, length) = written(3, 7);
+
+        let slot = open(&buffer[..length]).unwrap();
+        assert_eq!((slot.version, slot.sequence), (3, 7));
+
+        let mut reader = Reader(slot.payload);
+        assert_eq!(reader.i16(), Some(-40));
+        assert_eq!(reader.config().unwrap().diagonal, Diagonal::LastPressed);
+
+        let state = reader.state().unwrap();
+        assert!(state.flag(Flag(37)));
+        assert!(!state.flag(Flag(36)));
+        assert_eq!(state.vars[3], 9);
+
+        let bag: Inventory<char, 4> = reader.inventory(&ITEMS).unwrap();
+        assert_eq!(bag.slots(), &[Some(('c', 12)), Some(('a', 1)), None, None]);
+        assert_eq!(reader.u8(), None);
+    }
+
+    #[test]
+    fn corrupted_slots_are_skipped() {
+        let (newer, length) = written(1, 2);
+        let (older, _) = written(1, 1);
+
+        let mut corrupted = newer;
+        corrupted[HEADER_SIZE + 1] ^= 0x10;
+
+        assert_eq!(open(&corrupted), None);
+        assert_eq!(open(&[0xff; SLOT_SIZE]), None);
+
+        let sequences = [&corrupted, &older].map(|slot| open(slot).map(|slot| slot.sequence));
+        assert_eq!(newest(&sequences), Some(1));
+
+        let sequences = [&newer[..length], &older].map(|slot| open(slot).map(|slot| slot.sequence));
+        assert_eq!(newest(&sequences), Some(0));
+    }
+
+    #[test]
+    fn sequences_wrap() {
+        assert_eq!(newest(&[Some(u16::MAX), Some(0)]), Some(1));
+        assert_eq!(newest(&[Some(1), Some(u16::MAX)]), Some(0));
+        assert_eq!(newest(&[None, Some(5)]), Some(1));
+        assert_eq!(newest(&[None, None]), None);
+    }
+
+    #[test]
+    fn truncated_slots_dont_open() {
+        let (buffer, length) = written(1, 1);
+
+        assert!(open(&buffer[..length]).is_some());
+        assert_eq!(open(&buffer[..length - 1]), None);
+        assert_eq!(open(&buffer[..HEADER_SIZE - 1]), None);
+
+        assert_eq!(Reader(&[0x01]).u16(), None);
+        assert_eq!(Reader(&[1, 0, 2, 0]).config(), None);
+        assert_eq!(Reader(&[0, 12, 1]).inventory::<char, 2>(&ITEMS), None);
+    }
+
+    #[test]
+    fn bad_stacks_dont_load() {
+        assert_eq!(Reader(&[0, 0, 255, 0]).inventory::<char, 2>(&ITEMS), None);
+        assert_eq!(Reader(&[0, 100, 255, 0]).inventory::<char, 2>(&ITEMS), None);
+        assert_eq!(Reader(&[3, 1, 255, 0]).inventory::<char, 2>(&ITEMS), None);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::{
+    collision::BODY_OFFSET,
+    map::TILE_SIZE,
+    movement::MovementType,
+    state::{Condition, Flag, State, Var},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Actor {
+    Player,
+    // By index into the map's npcs.
+    Npc(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fade {
+    Out,
+    In,
+}
+
+// Tiles are where the top left of the actor's body ends up, like warp spawns.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    // Walks through each tile in turn, across then down, a pixel a frame.
+    Move(Actor, &'static [(i32, i32)]),
+    Face(Actor, MovementType),
+    // Waits for the player to close it.
+    Say(&'static str),
+    Wait(u16),
+    Set(Flag),
+    Clear(Flag),
+    Add(Var, i16),
+    // Skips the next so many commands unless the condition holds.
+    If(Condition, usize),
+    Fade(Fade, u16),
+    Warp(&'static str, (i32, i32)),
+}
+
+// Made by mapgen's generate_scripts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Script {
+    pub name: &'static str,
+    pub commands: &'static [Command],
+}
+
+// What a cutscene acts on. While `busy` the cutscene doesn't move on, which is
+// how it waits for dialogue, fades and warps to finish. `position` is None for
+// an npc the map doesn't have, and moves of that npc are skipped. `place` is
+// false when something's in the way, which ends the move where it got to.
+pub trait Stage {
+    fn position(&mut self, actor: Actor) -> Option<(i32, i32)>;
+    fn place(&mut self, actor: Actor, position: (i32, i32)) -> bool;
+    fn face(&mut self, actor: Actor, facing: MovementType);
+    fn say(&mut self, text: &'static str);
+    fn state(&mut self) -> &mut State;
+    fn fade(&mut self, fade: Fade, frames: u16);
+    fn warp(&mut self, map: &'static str, spawn: (i32, i32));
+    fn busy(&self) -> bool;
+}
+
+struct Walk {
+    actor: Actor,
+    path: &'static [(i32, i32)],
+    next: usize,
+}
+
+// Runs a script a frame at a time against a stage.
+pub struct Cutscene {
+    script: &'static Script,
+    next: usize,
+    wait: u16,
+    walk: Option<Walk>,
+}
+
+impl Cutscene {
+    pub fn new(script: &'static Script) -> Self {
+        Self {
+            script,
+            next: 0,
+            wait: 0,
+            walk: None,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.script.commands.len() && self.wait == 0 && self.walk.is_none()
+    }
+
+    // Commands that finish straight away run together, the rest take this frame
+    // and hold the script up until they're done.
+    pub fn tick(&mut self, stage: &mut impl Stage) {
+        if stage.busy() {
+            return;
+        }
+
+        if self.wait > 0 {
+            self.wait -= 1;
+            return;
+        }
+
+        if let Some(walk) = self.walk.as_mut() {
+            if walk_towards(walk, stage) {
+                self.walk = None;
+            }
+            return;
+        }
+
+        while let Some(&command) = self.script.commands.get(self.next) {
+            self.next += 1;
+
+            match command {
+                Command::Move(actor, path) => {
+                    self.walk = Some(Walk {
+                        actor,
+                        path,
+                        next: 0,
+                    });
+                    return;
+                }
+                Command::Face(actor, facing) => stage.face(actor, facing),
+                Command::Say(text) => return stage.say(text),
+                Command::Wait(frames) => {
+                    self.wait = frames;
+                    return;
+                }
+                Command::Set(flag) => stage.state().set(flag, true),
+                Command::Clear(flag) => stage.state().set(flag, false),
+                Command::Add(var, amount) => stage.state().add(var, amount),
+                Command::If(condition, skip) => {
+                    if !stage.state().check(condition) {
+                        self.next += skip;
+                    }
+                }
+                Command::Fade(fade, frames) => return stage.fade(fade, frames),
+                Command::Warp(map, spawn) => return stage.warp(map, spawn),
+            }
+        }
+    }
+}
+
+// Moves the actor a pixel towards the next tile of the path, true once it's
+// stood on the last one.
+fn walk_towards(walk: &mut Walk, stage: &mut impl Stage) -> bool {
+    let Some(&(x, y)) = walk.path.get(walk.next) else {
+        return true;
+    };
+    let target = (x * TILE_SIZE - BODY_OFFSET.0, y * TILE_SIZE - BODY_OFFSET.1);
+    let Some((x, y)) = stage.position(walk.actor) else {
+        return true;
+    };
+
+    let (position, facing) = if x != target.0 {
+        let facing = if target.0 > x {
+            MovementType::Right
+        } else {
+            MovementType::Left
+        };
+        ((x + (target.0 - x).signum(), y), facing)
+    } else if y != target.1 {
+        let facing = if target.1 > y {
+            MovementType::Down
+        } else {
+            MovementType::Up
+        };
+        ((x, y + (target.1 - y).signum()), facing)
+    } else {
+        walk.next += 1;
+        return walk.next >= walk.path.len();
+    };
+
+    stage.face(walk.actor, facing);
+    if !stage.place(walk.actor, position) {
+        return true;
+    }
+
+    if position == target {
+        walk.next += 1;
+    }
+    walk.next >= walk.path.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[derive(Default)]
+    struct Recorder {
+        player: (i32, i32),
+        facing: Vec<MovementType>,
+        said: Vec<&'static str>,
+        state: State,
+        fades: Vec<(Fade, u16)>,
+        warps: Vec<(&'static str, (i32, i32))>,
+        busy: bool,
+        // Nothing can stand at or right of this x.
+        wall: Option<i32>,
+    }
+
+    impl Stage for Recorder {
+        fn position(&mut self, actor: Actor) -> Option<(i32, i32)> {
+            (actor == Actor::Player).then_some(self.player)
+        }
+
+        fn place(&mut self, _: Actor, position: (i32, i32)) -> bool {
+            let blocked = self.wall.is_some_and(|wall| position.0 >= wall);
+            if !blocked {
+                self.player = position;
+            }
+
+            !blocked
+        }
+
+        fn face(&mut self, _: Actor, facing: MovementType) {
+            if self.facing.last() != Some(&facing) {
+                self.facing.push(facing);
+            }
+        }
+
+        fn say(&mut self, text: &'static str) {
+            self.said.push(text);
+        }
+
+        fn state(&mut self) -> &mut State {
+            &mut self.state
+        }
+
+        fn fade(&mut self, fade: Fade, frames: u16) {
+            self.fades.push((fade, frames));
+        }
+
+        fn warp(&mut self, map: &'static str, spawn: (i32, i32)) {
+            self.warps.push((map, spawn));
+        }
+
+        fn busy(&self) -> bool {
+            self.busy
+        }
+    }
+
+    fn run(script: &'static Script, stage: &mut Recorder) -> usize {
+        let mut cutscene = Cutscene::new(script);
+        let mut frames = 0;
+
+        while !cutscene.finished() {
+            cutscene.tick(stage);
+            frames += 1;
+            assert!(frames < 1000, "script never finished");
+        }
+
+        frames
+    }
+
+    #[test]
+    fn moves_walk_across_then_down_a_pixel_a_frame() {
+        static SCRIPT: Script = Script {
+            name: "walk",
+            commands: &[Command::Move(Actor::Player, &[(3, 2), (2, 2)])],
+        };
+        let mut stage = Recorder::default();
+
+        let frames = run(&SCRIPT, &mut stage);
+
+        assert_eq!(stage.player, (8, 8));
+        // 16 right, 8 down, 8 left and a frame to start walking.
+        assert_eq!(frames, 33);
+        assert_eq!(
+            stage.facing,
+            [MovementType::Right, MovementType::Down, MovementType::Left]
+        );
+    }
+
+    #[test]
+    fn instant_commands_run_together_and_waits_hold_the_script() {
+        const SEEN: Flag = Flag(0);
+        const WAITED: Flag = Flag(1);
+        static SCRIPT: Script = Script {
+            name: "wait",
+            commands: &[
+                Command::Face(Actor::Player, MovementType::Up),
+                Command::Set(SEEN),
+                Command::Wait(10),
+                Command::Set(WAITED),
+            ],
+        };
+        let mut stage = Recorder::default();
+        let mut cutscene = Cutscene::new(&SCRIPT);
+
+        cutscene.tick(&mut stage);
+        assert_eq!(stage.facing, [MovementType::Up]);
+        assert!(stage.state.flag(SEEN));
+
+        for _ in 0..10 {
+            cutscene.tick(&mut stage);
+        }
+        assert!(!stage.state.flag(WAITED));
+
+        cutscene.tick(&mut stage);
+        assert!(stage.state.flag(WAITED));
+        assert!(cutscene.finished());
+    }
+
+    #[test]
+    fn ifs_skip_their_block_when_the_condition_fails() {
+        const MET: Flag = Flag(3);
+        const VISITS: Var = Var(0);
+        static SCRIPT: Script = Script {
+            name: "if",
+            commands: &[
+                Command::If(Condition::Set(MET), 2),
+                Command::Say("Welcome back."),
+                Command::Add(VISITS, 1),
+                Command::If(Condition::Unset(MET), 2),
+                Command::Say("Nice to meet you."),
+                Command::Set(MET),
+            ],
+        };
+
+        let mut stage = Recorder::default();
+        run(&SCRIPT, &mut stage);
+        assert_eq!(stage.said, ["Nice to meet you."]);
+        assert_eq!(stage.state.var(VISITS), 0);
+
+        let mut stage = Recorder {
+            state: stage.state,
+            ..Recorder::default()
+        };
+        run(&SCRIPT, &mut stage);
+        assert_eq!(stage.said, ["Welcome back."]);
+        assert_eq!(stage.state.var(VISITS), 1);
+    }
+
+    #[test]
+    fn a_busy_stage_holds_the_script() {
+        static SCRIPT: Script = Script {
+            name: "busy",
+            commands: &[
+                Command::Say("Hello."),
+                Command::Fade(Fade::Out, 16),
+                Command::Warp("house-1", (14, 18)),
+                Command::Say("Bye."),
+            ],
+        };
+        let mut stage = Recorder::default();
+        let mut cutscene = Cutscene::new(&SCRIPT);
+
+        cutscene.tick(&mut stage);
+        stage.busy = true;
+        for _ in 0..5 {
+            cutscene.tick(&mut stage);
+        }
+        assert_eq!(stage.said, ["Hello."]);
+        assert_eq!(stage.fades, []);
+
+        stage.busy = false;
+        for _ in 0..3 {
+            cutscene.tick(&mut stage);
+        }
+
+        assert_eq!(stage.said, ["Hello.", "Bye."]);
+        assert_eq!(stage.fades, [(Fade::Out, 16)]);
+        assert_eq!(stage.warps, [("house-1", (14, 18))]);
+        assert!(cutscene.finished());
+    }
+
+    #[test]
+    fn moving_a_missing_npc_is_skipped() {
+        static SCRIPT: Script = Script {
+            name: "missing",
+            commands: &[
+                Command::Move(Actor::Npc(4), &[(3, 2)]),
+                Command::Say("Anyone there?"),
+            ],
+        };
+        let mut stage = Recorder::default();
+
+        run(&SCRIPT, &mut stage);
+
+        assert_eq!(stage.player, (0, 0));
+        assert_eq!(stage.facing, []);
+        assert_eq!(stage.said, ["Anyone there?"]);
+    }
+
+    #[test]
+    fn blocked_moves_stop_where_they_got_to() {
+        static SCRIPT: Script = Script {
+            name: "blocked",
+            commands: &[
+                Command::Move(Actor::Player, &[(3, 2), (3, 4)]),
+                Command::Say("Ouch."),
+            ],
+        };
+        let mut stage = Recorder {
+            wall: Some(10),
+            ..Recorder::default()
+        };
+
+        let frames = run(&SCRIPT, &mut stage);
+
+        assert_eq!(stage.player, (9, 0));
+        assert_eq!(stage.said, ["Ouch."]);
+        // A frame to start walking, 9 right, one bumping into the wall and one
+        // to speak.
+        assert_eq!(frames, 12);
+    }
+}
@@ -0,0 +1,121 @@
+// Story flags and small counters that outlive a map, and go in the save. Each
+// one is named in gfx/state.names, and mapgen turns the names into consts so a
+// typo in a script or a map file doesn't build.
+pub const FLAG_WORDS: usize = 4;
+pub const FLAGS: usize = FLAG_WORDS * 32;
+pub const VARS: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Flag(pub u8);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Var(pub u8);
+
+// Gates dialogue, triggers and doors on the state.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Condition {
+    Always,
+    Set(Flag),
+    Unset(Flag),
+    AtLeast(Var, u8),
+    Below(Var, u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct State {
+    pub flags: [u32; FLAG_WORDS],
+    pub vars: [u8; VARS],
+}
+
+impl State {
+    pub fn flag(&self, Flag(flag): Flag) -> bool {
+        self.flags[flag as usize / 32] & (1 << (flag % 32)) != 0
+    }
+
+    pub fn set(&mut self, Flag(flag): Flag, on: bool) {
+        let word = &mut self.flags[flag as usize / 32];
+
+        if on {
+            *word |= 1 << (flag % 32);
+        } else {
+            *word &= !(1 << (flag % 32));
+        }
+    }
+
+    pub fn var(&self, Var(var): Var) -> u8 {
+        self.vars[var as usize]
+    }
+
+    pub fn set_var(&mut self, Var(var): Var, value: u8) {
+        self.vars[var as usize] = value;
+    }
+
+    // Counters stop at 0 and 255 rather than wrapping.
+    pub fn add(&mut self, var: Var, amount: i16) {
+        let value = (self.var(var) as i16 + amount).clamp(0, u8::MAX as i16);
+        self.set_var(var, value as u8);
+    }
+
+    pub fn check(&self, condition: Condition) -> bool {
+        match condition {
+            Condition::Always => true,
+            Condition::Set(flag) => self.flag(flag),
+            Condition::Unset(flag) => !self.flag(flag),
+            Condition::AtLeast(var, value) => self.var(var) >= value,
+            Condition::Below(var, value) => self.var(var) < value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_are_independent_bits() {
+        let mut state = State::default();
+
+        state.set(Flag(0), true);
+        state.set(Flag(33), true);
+        state.set(Flag(127), true);
+
+        assert!(state.flag(Flag(0)) && state.flag(Flag(33)) && state.flag(Flag(127)));
+        assert!(!state.flag(Flag(1)) && !state.flag(Flag(32)));
+        assert_eq!(state.flags, [1, 2, 0, 1 << 31]);
+
+        state.set(Flag(33), false);
+        assert!(!state.flag(Flag(33)));
+        assert!(state.flag(Flag(0)));
+    }
+
+    #[test]
+    fn vars_add_without_wrapping() {
+        let mut state = State::default();
+
+        state.add(Var(3), 5);
+        assert_eq!(state.var(Var(3)), 5);
+
+        state.add(Var(3), -10);
+        assert_eq!(state.var(Var(3)), 0);
+
+        state.add(Var(3), 300);
+        assert_eq!(state.var(Var(3)), 255);
+        assert_eq!(state.var(Var(2)), 0);
+    }
+
+    #[test]
+    fn conditions_read_flags_and_vars() {
+        let mut state = State::default();
+        state.set(Flag(4), true);
+        state.set_var(Var(1), 3);
+
+        assert!(state.check(Condition::Always));
+        assert!(state.check(Condition::Set(Flag(4))));
+        assert!(!state.check(Condition::Unset(Flag(4))));
+        assert!(state.check(Condition::Unset(Flag(5))));
+        assert!(state.check(Condition::AtLeast(Var(1), 3)));
+        assert!(!state.check(Condition::AtLeast(Var(1), 4)));
+        assert!(state.check(Condition::Below(Var(1), 4)));
+        assert!(!state.check(Condition::Below(Var(1), 3)));
+    }
+}
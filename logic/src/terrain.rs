@@ -0,0 +1,152 @@
+use agb_fixnum::Vector2D;
+use mapgen::generate_tile_attributes;
+
+use crate::map::{tile_at, tile_of};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Terrain {
+    Wall,
+    Grass,
+    TallGrass,
+    Water,
+    Ledge,
+    Ice,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Footstep {
+    None,
+    Soft,
+    Hard,
+    Splash,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Attributes {
+    pub terrain: Terrain,
+    pub footstep: Footstep,
+    // Whether wild encounters can start on it.
+    pub encounters: bool,
+}
+
+impl Attributes {
+    pub const WALL: Self = Self {
+        terrain: Terrain::Wall,
+        footstep: Footstep::None,
+        encounters: false,
+    };
+
+    // Open ground, for places without a map.
+    pub const GRASS: Self = Self {
+        terrain: Terrain::Grass,
+        footstep: Footstep::Soft,
+        encounters: false,
+    };
+
+    // Ground that can be stood on and crossed in any direction.
+    pub const fn walkable(&self) -> bool {
+        matches!(
+            self.terrain,
+            Terrain::Grass | Terrain::TallGrass | Terrain::Ice
+        )
+    }
+
+    // Whether the player moving by `(dx, dy)` can go onto the tile. Ledges can
+    // only be jumped down, water needs swimming.
+    pub fn enterable(&self, (dx, dy): (i32, i32), rules: Rules) -> bool {
+        match self.terrain {
+            Terrain::Water => rules.swimming,
+            Terrain::Ledge => dx == 0 && dy > 0,
+            _ => self.walkable(),
+        }
+    }
+}
+
+// What the player is able to do that changes where they can go.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Rules {
+    pub swimming: bool,
+}
+
+// One entry per tile of gfx/bg-tiles.png, from gfx/bg-tiles.legend.
+static ATTRIBUTES: &[Attributes] = &generate_tile_attributes!("../gfx/bg-tiles.legend");
+
+// Anything past the end of the legend, or off the map, is a wall.
+pub const fn attributes(tile: usize) -> Attributes {
+    if tile < ATTRIBUTES.len() {
+        ATTRIBUTES[tile]
+    } else {
+        Attributes::WALL
+    }
+}
+
+pub fn attributes_at<const W: usize, const H: usize>(
+    tilemap: &[[usize; W]; H],
+    point: Vector2D<i32>,
+) -> Attributes {
+    tile_at(tilemap, tile_of(point)).map_or(Attributes::WALL, attributes)
+}
+
+pub fn terrain_at<const W: usize, const H: usize>(
+    tilemap: &[[usize; W]; H],
+    point: Vector2D<i32>,
+) -> Terrain {
+    attributes_at(tilemap, point).terrain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_legend_covers_the_tileset() {
+        assert_eq!(attributes(0), Attributes::WALL);
+        assert_eq!(attributes(3).terrain, Terrain::Wall);
+        assert_eq!(
+            attributes(5),
+            Attributes {
+                terrain: Terrain::TallGrass,
+                footstep: Footstep::Soft,
+                encounters: true,
+            }
+        );
+        assert_eq!(attributes(10).terrain, Terrain::Water);
+        assert_eq!(attributes(10).footstep, Footstep::Splash);
+        assert_eq!(attributes(11).terrain, Terrain::Ledge);
+        assert_eq!(attributes(12).terrain, Terrain::Ice);
+        assert_eq!(attributes(1000), Attributes::WALL);
+    }
+
+    #[test]
+    fn terrain_is_looked_up_by_world_position() {
+        let tilemap = [[0, 5], [10, 5]];
+
+        assert_eq!(terrain_at(&tilemap, (8, 0).into()), Terrain::TallGrass);
+        assert_eq!(terrain_at(&tilemap, (7, 15).into()), Terrain::Water);
+        assert_eq!(terrain_at(&tilemap, (0, 0).into()), Terrain::Wall);
+        assert_eq!(terrain_at(&tilemap, (-1, 0).into()), Terrain::Wall);
+        assert_eq!(terrain_at(&tilemap, (16, 0).into()), Terrain::Wall);
+    }
+
+    #[test]
+    fn only_ground_you_can_stand_on_is_walkable() {
+        assert!(attributes(5).walkable());
+        assert!(!attributes(10).walkable());
+        assert!(!attributes(11).walkable());
+        assert!(!Attributes::WALL.walkable());
+    }
+
+    #[test]
+    fn ledges_only_go_down_and_water_needs_swimming() {
+        let ledge = attributes(11);
+        let water = attributes(10);
+        let swimming = Rules { swimming: true };
+
+        assert!(ledge.enterable((0, 1), Rules::default()));
+        assert!(!ledge.enterable((0, -1), Rules::default()));
+        assert!(!ledge.enterable((1, 0), Rules::default()));
+        assert!(!water.enterable((0, 1), Rules::default()));
+        assert!(water.enterable((0, 1), swimming));
+        assert!(attributes(12).enterable((-1, 0), Rules::default()));
+    }
+}
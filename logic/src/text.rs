@@ -0,0 +1,64 @@
+// Splits text into lines of at most `width` characters, breaking between words.
+// A word too long for a line gets one to itself and runs over.
+pub fn wrap(text: &str, width: usize) -> Wrap<'_> {
+    Wrap { rest: text, width }
+}
+
+pub struct Wrap<'a> {
+    rest: &'a str,
+    width: usize,
+}
+
+impl<'a> Iterator for Wrap<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+
+        // Where each word ends, the last one at the end of the text.
+        let ends = rest
+            .match_indices(' ')
+            .map(|(index, _)| index)
+            .chain([rest.len()]);
+
+        let mut end = 0;
+        for index in ends {
+            if end > 0 && rest[..index].chars().count() > self.width {
+                break;
+            }
+            end = index;
+        }
+
+        self.rest = &rest[end..];
+        Some(&rest[..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn breaks_between_words() {
+        let lines: Vec<_> = wrap("Everything's just as I left it.", 16).collect();
+
+        assert_eq!(lines, ["Everything's", "just as I left", "it."]);
+    }
+
+    #[test]
+    fn long_words_run_over() {
+        let lines: Vec<_> = wrap("a dishwasher", 4).collect();
+
+        assert_eq!(lines, ["a", "dishwasher"]);
+    }
+
+    #[test]
+    fn nothing_to_say_is_no_lines() {
+        assert_eq!(wrap("  ", 8).next(), None);
+        assert_eq!(wrap("Hi", 8).collect::<Vec<_>>(), ["Hi"]);
+    }
+}
@@ -0,0 +1,281 @@
+use alloc::vec::Vec;
+
+use agb_fixnum::Rect;
+
+use crate::{
+    collision::center,
+    map::{tile_of, TILE_SIZE},
+    rng::Rng,
+    state::{Condition, State},
+    terrain::Attributes,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum When {
+    Enter,
+    Exit,
+    // Every time the middle of the body moves onto another tile inside the zone,
+    // as long as wild encounters can start on it.
+    Step,
+}
+
+// A rectangle of the map, in tiles, that fires `event` when the player's body
+// does `when` with it and the condition holds. Only `chance` percent of those
+// fire, which is how encounter rolls work. Made by mapgen's
+// generate_map_triggers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Zone<E: 'static> {
+    pub area: (i32, i32, i32, i32),
+    pub when: When,
+    pub chance: u8,
+    pub condition: Condition,
+    pub event: E,
+}
+
+impl<E> Zone<E> {
+    pub fn rect(&self) -> Rect<i32> {
+        let (x, y, width, height) = self.area;
+
+        Rect::new(
+            (x * TILE_SIZE, y * TILE_SIZE).into(),
+            (width * TILE_SIZE, height * TILE_SIZE).into(),
+        )
+    }
+}
+
+// Which zones the player is in, checked against their body every logic tick.
+pub struct Triggers<E: 'static> {
+    zones: &'static [Zone<E>],
+    inside: Vec<bool>,
+    tile: (i32, i32),
+}
+
+impl<E> Triggers<E> {
+    // Zones the body starts in count as already entered, so arriving on a map
+    // doesn't set them off.
+    pub fn new(zones: &'static [Zone<E>], body: Rect<i32>) -> Self {
+        Self {
+            zones,
+            inside: zones.iter().map(|zone| zone.rect().touches(body)).collect(),
+            tile: tile_of(center(body)),
+        }
+    }
+
+    // Fills `fired` with the events that fire for the body being at `body` now,
+    // in map order. `ground` is the tile under the middle of the body. The
+    // buffer is the caller's so nothing is allocated every frame. True if the
+    // middle of the body stepped onto another tile.
+    pub fn update(
+        &mut self,
+        body: Rect<i32>,
+        ground: Attributes,
+        state: &State,
+        rng: &mut Rng,
+        fired: &mut Vec<&'static E>,
+    ) -> bool {
+        let tile = tile_of(center(body));
+        let stepped = tile != self.tile;
+        self.tile = tile;
+
+        fired.clear();
+        for (zone, inside) in self.zones.iter().zip(self.inside.iter_mut()) {
+            let was_inside = *inside;
+            *inside = zone.rect().touches(body);
+
+            let fires = match zone.when {
+                When::Enter => *inside && !was_inside,
+                When::Exit => was_inside && !*inside,
+                When::Step => *inside && stepped && ground.encounters,
+            };
+
+            if fires && state.check(zone.condition) && rng.roll(zone.chance) {
+                fired.push(&zone.event);
+            }
+        }
+
+        stepped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        collision::body,
+        state::Flag,
+        terrain::{Footstep, Terrain},
+    };
+
+    const TALL_GRASS: Attributes = Attributes {
+        terrain: Terrain::TallGrass,
+        footstep: Footstep::Soft,
+        encounters: true,
+    };
+
+    fn fired(
+        triggers: &mut Triggers<&str>,
+        body: Rect<i32>,
+        ground: Attributes,
+        state: &State,
+        rng: &mut Rng,
+    ) -> Vec<&'static str> {
+        let mut fired = Vec::new();
+        triggers.update(body, ground, state, rng, &mut fired);
+
+        fired.into_iter().copied().collect()
+    }
+
+    const ZONES: &[Zone<&str>] = &[
+        Zone {
+            area: (4, 4, 4, 4),
+            when: When::Enter,
+            chance: 100,
+            condition: Condition::Always,
+            event: "enter",
+        },
+        Zone {
+            area: (4, 4, 4, 4),
+            when: When::Exit,
+            chance: 100,
+            condition: Condition::Always,
+            event: "exit",
+        },
+        Zone {
+            area: (4, 4, 4, 4),
+            when: When::Step,
+            chance: 100,
+            condition: Condition::Always,
+            event: "step",
+        },
+    ];
+
+    fn walk(triggers: &mut Triggers<&str>, rng: &mut Rng, xs: &[i32]) -> Vec<&'static str> {
+        xs.iter()
+            .flat_map(|&x| {
+                let body = body((x, 16).into());
+                fired(triggers, body, TALL_GRASS, &State::default(), rng)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zones_fire_on_enter_step_and_exit() {
+        let mut rng = Rng::new(1);
+        // The body spans x + 8 to x + 32, so it overlaps the zone from x = 1 to 55.
+        let mut triggers = Triggers::new(ZONES, body((0, 16).into()));
+
+        assert_eq!(walk(&mut triggers, &mut rng, &[0]), [] as [&str; 0]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[1]), ["enter"]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[2, 3, 4]), ["step"]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[12, 20]), ["step", "step"]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[55]), ["step"]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[56]), ["exit"]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[64]), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn starting_inside_doesnt_count_as_entering() {
+        let mut rng = Rng::new(1);
+        let mut triggers = Triggers::new(ZONES, body((16, 16).into()));
+
+        assert_eq!(walk(&mut triggers, &mut rng, &[16, 17]), [] as [&str; 0]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[40]), ["step"]);
+        assert_eq!(walk(&mut triggers, &mut rng, &[64]), ["exit"]);
+    }
+
+    #[test]
+    fn chance_rolls_follow_the_seed() {
+        const GRASS: &[Zone<&str>] = &[Zone {
+            area: (0, 0, 32, 32),
+            when: When::Step,
+            chance: 30,
+            condition: Condition::Always,
+            event: "encounter",
+        }];
+
+        let encounters = |seed| {
+            let mut rng = Rng::new(seed);
+            let mut triggers = Triggers::new(GRASS, body((0, 0).into()));
+            let xs: Vec<i32> = (1..=100).map(|step| step * TILE_SIZE % 200).collect();
+
+            walk(&mut triggers, &mut rng, &xs).len()
+        };
+
+        assert_eq!(encounters(5), encounters(5));
+        assert!((10..50).contains(&encounters(5)));
+        assert!((10..50).contains(&encounters(6)));
+    }
+
+    #[test]
+    fn conditions_gate_zones() {
+        const SEEN: Flag = Flag(2);
+        const ONCE: &[Zone<&str>] = &[Zone {
+            area: (4, 4, 4, 4),
+            when: When::Enter,
+            chance: 100,
+            condition: Condition::Unset(SEEN),
+            event: "first time",
+        }];
+
+        let mut rng = Rng::new(1);
+        let mut state = State::default();
+        let mut triggers = Triggers::new(ONCE, body((0, 16).into()));
+        let mut at = |x: i32, state: &State| {
+            fired(
+                &mut triggers,
+                body((x, 16).into()),
+                TALL_GRASS,
+                state,
+                &mut rng,
+            )
+        };
+
+        assert_eq!(at(8, &state), ["first time"]);
+        state.set(SEEN, true);
+        at(64, &state);
+        assert_eq!(at(8, &state), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn steps_only_fire_where_encounters_can_start() {
+        let mut rng = Rng::new(1);
+        let mut triggers = Triggers::new(&ZONES[2..], body((16, 16).into()));
+        let mut at = |x: i32, ground| {
+            fired(
+                &mut triggers,
+                body((x, 16).into()),
+                ground,
+                &State::default(),
+                &mut rng,
+            )
+        };
+
+        assert_eq!(at(24, Attributes::GRASS), [] as [&str; 0]);
+        assert_eq!(at(32, TALL_GRASS), ["step"]);
+    }
+
+    #[test]
+    fn updates_say_when_the_body_steps_onto_another_tile() {
+        let mut rng = Rng::new(1);
+        let mut fired = Vec::new();
+        let mut triggers = Triggers::<&str>::new(&[], body((0, 16).into()));
+        let mut at = |x: i32| {
+            triggers.update(
+                body((x, 16).into()),
+                TALL_GRASS,
+                &State::default(),
+                &mut rng,
+                &mut fired,
+            )
+        };
+
+        // The middle of the body is at x + 20, on the next tile from x = 4.
+        assert!(!at(0));
+        assert!(!at(3));
+        assert!(at(4));
+        assert!(!at(11));
+        assert!(at(12));
+        assert!(at(4));
+    }
+}
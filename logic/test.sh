@@ -0,0 +1,7 @@
+#!/bin/sh
+# Runs the logic crate's tests on the host. Cargo merges every .cargo/config.toml
+# above the directory it's run from, and the game's builds core for the GBA, so
+# the tests are run from outside the repo.
+logic=$(cd "$(dirname "$0")" && pwd)
+
+cd / && exec cargo test --manifest-path "$logic/Cargo.toml" --target-dir "$logic/target" "$@"
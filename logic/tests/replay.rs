@@ -0,0 +1,157 @@
+// Golden runs: input recorded from the game (hold L and press R to start and
+// stop, the mgba log has the start position, the recording and where it ended)
+// replayed against the real maps. NPCs aren't simulated, so keep recordings
+// clear of them.
+
+use logic::{
+    input::{Action, Config, Diagonal, Input, Recording},
+    metatile::MetatileMap,
+    motion::Motion,
+    movement::{GridWalker, MovementType},
+    terrain::{Attributes, Rules},
+};
+
+use mapgen::{generate_background_map, generate_metatile_map};
+
+const BG: [[usize; 32]; 32] = generate_background_map!("../gfx/bg.png", "../gfx/bg-tiles.png");
+const HOUSE_1: [[usize; 32]; 32] =
+    generate_background_map!("../gfx/house-1.png", "../gfx/bg-tiles.png");
+const HOUSE_1_METATILES: MetatileMap =
+    generate_metatile_map!("../gfx/house-1.png", "../gfx/bg-tiles.png", 2);
+
+// Where app.rs puts the player before any scrolling.
+const START: (i16, i16) = (104, 64);
+
+fn replay(tilemap: &[[usize; 32]; 32], start: (i16, i16), recording: &str) -> (i16, i16) {
+    let recording = Recording::parse(recording).unwrap();
+    let mut input = Input::new(Config::default());
+    let mut motion = Motion::new(start);
+
+    for pressed in recording.frames() {
+        input.update(pressed);
+        motion.steer(input.movement(), input.is_held(Action::Run));
+        motion.advance(tilemap, Rules::default(), |_| false);
+    }
+
+    motion.pixel()
+}
+
+fn replay_grid(recording: &str) -> GridWalker {
+    let recording = Recording::parse(recording).unwrap();
+    let mut input = Input::new(Config {
+        diagonal: Diagonal::LastPressed,
+        ..Config::default()
+    });
+    let mut walker = GridWalker::new((104, 64), 32);
+
+    for pressed in recording.frames() {
+        input.update(pressed);
+        // app_bc has no map.
+        walker.frame(input.movement(), Rules::default(), |_| Attributes::GRASS);
+    }
+
+    walker
+}
+
+#[test]
+fn idle_frames_and_other_buttons_dont_move() {
+    assert_eq!(replay(&BG, START, "0*60 1*1 0*10 4*1 8*30"), START);
+}
+
+#[test]
+fn walking_stops_at_each_edge_of_the_field() {
+    assert_eq!(replay(&BG, START, "20*200"), (33, 64));
+    assert_eq!(replay(&BG, START, "40*200"), (104, 33));
+    assert_eq!(replay(&BG, START, "10*200"), (191, 64));
+    assert_eq!(replay(&BG, START, "80*200"), (104, 191));
+}
+
+#[test]
+fn diagonals_slide_along_walls() {
+    // Up and left together, the top wall stops y but x carries on.
+    assert_eq!(replay(&BG, START, "60*40"), (65, 33));
+    assert_eq!(replay(&BG, START, "60*300"), (33, 33));
+}
+
+#[test]
+fn running_covers_more_ground_and_coasts_to_a_stop() {
+    // B held with right, speeding up over 8 frames then 2px a frame.
+    assert_eq!(replay(&BG, START, "12*30"), (157, 64));
+    assert_eq!(replay(&BG, START, "12*30 0*20"), (160, 64));
+    assert_eq!(replay(&BG, START, "10*30 0*20"), (133, 64));
+}
+
+#[test]
+fn running_into_walls_stops_in_the_same_place_as_walking() {
+    assert_eq!(replay(&BG, START, "22*200"), (33, 64));
+    assert_eq!(replay(&BG, START, "12*200"), (191, 64));
+    assert_eq!(replay(&BG, START, "82*200"), (104, 191));
+    assert_eq!(replay(&HOUSE_1, (104, 112), "92*300"), (151, 151));
+}
+
+#[test]
+fn ledges_hop_down_and_water_stops() {
+    // Over the ice, down the ledge at row 19, and up to the pond at row 23.
+    assert_eq!(replay(&BG, (128, 40), "80*300"), (128, 159));
+    // Back up as far as the foot of the ledge.
+    assert_eq!(replay(&BG, (128, 159), "40*300"), (128, 153));
+}
+
+#[test]
+fn ice_carries_on_after_letting_go() {
+    // The ice is columns 17 to 19, the player slides until the middle of the
+    // body is off it and then slows down as usual.
+    assert_eq!(replay(&BG, (128, 96), "20*2 0*60"), (115, 96));
+    assert_eq!(replay(&BG, (104, 96), "10*30 0*60"), (141, 96));
+}
+
+#[test]
+fn walks_a_loop_back_to_the_start() {
+    assert_eq!(replay(&BG, START, "10*48 80*24 20*48 40*24"), START);
+}
+
+#[test]
+fn house_walls_hold() {
+    let start = (104, 112);
+
+    assert_eq!(replay(&HOUSE_1, start, "20*300"), (73, 112));
+    assert_eq!(replay(&HOUSE_1, start, "90*300"), (151, 151));
+}
+
+#[test]
+fn house_metatiles_match_its_tiles() {
+    assert_eq!(HOUSE_1_METATILES.expand::<32, 32>(), HOUSE_1);
+}
+
+#[test]
+fn grid_steps_are_whole_and_y_wins() {
+    let walker = replay_grid("10*1 0*40");
+    assert_eq!(walker.position, (136, 64));
+    assert_eq!(walker.movement, None);
+
+    let walker = replay_grid("50*1 0*40");
+    assert_eq!(walker.position, (104, 32));
+
+    let walker = replay_grid("80*60 0*40");
+    assert_eq!(walker.position, (104, 128));
+}
+
+#[test]
+fn grid_turns_to_the_last_direction_pressed() {
+    // Holding down, then adding right while still holding down.
+    let walker = replay_grid("80*20 90*40 0*40");
+    assert_eq!(walker.position, (104 + 32, 64 + 32));
+}
+
+#[test]
+fn grid_input_mid_step_is_ignored() {
+    let walker = replay_grid("10*1 80*10 0*40");
+    assert_eq!(walker.position, (136, 64));
+    assert_eq!(walker.movement, None);
+
+    let walker = replay_grid("10*1 80*10");
+    assert_eq!(
+        walker.movement.map(|movement| movement.tp),
+        Some(MovementType::Right)
+    );
+}
@@ -1,42 +1,59 @@
-use image::{ImageReader, Rgb};
+use image::{ImageReader, Rgb, RgbImage};
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{LitStr, parse_macro_input};
+use proc_macro2::Literal;
+use quote::{format_ident, quote};
+use std::path::{Path, PathBuf};
+use syn::{
+    Ident, Lit, LitStr, Token,
+    parse::{Parse, ParseStream, Parser},
+    parse_macro_input,
+    punctuated::Punctuated,
+};
+
+mod quantise;
 
 const TILE_SIZE: u32 = 8;
 
-#[proc_macro]
-pub fn generate_background_map(input: TokenStream) -> TokenStream {
-    let processed = parse_macro_input!(input as LitStr);
+fn tile_at(img: &RgbImage, m: u32, n: u32) -> Vec<&Rgb<u8>> {
+    let mut tile = vec![];
 
-    let img = ImageReader::open(processed.value())
-        .unwrap()
-        .decode()
-        .unwrap();
-    let img_rgb8 = img.to_rgb8();
+    for i in 0..TILE_SIZE {
+        for r in 0..TILE_SIZE {
+            tile.push(img.get_pixel(m * TILE_SIZE + i, n * TILE_SIZE + r))
+        }
+    }
 
-    let x = img_rgb8.width() / TILE_SIZE;
-    let y = img_rgb8.height() / TILE_SIZE;
+    tile
+}
 
+// Without a tileset the indices are handed out in order of first appearance, with
+// one they point at the matching tile of the tileset image instead.
+fn tile_indices(img: &RgbImage, tileset: Option<&RgbImage>) -> Vec<Vec<usize>> {
     let mut uniq_tiles: Vec<Vec<&Rgb<u8>>> = vec![];
+    if let Some(tileset) = tileset {
+        for n in 0..tileset.height() / TILE_SIZE {
+            for m in 0..tileset.width() / TILE_SIZE {
+                uniq_tiles.push(tile_at(tileset, m, n));
+            }
+        }
+    }
+
+    let x = img.width() / TILE_SIZE;
+    let y = img.height() / TILE_SIZE;
+
     let mut result = vec![];
     for n in 0..y {
         let mut rows = vec![];
 
         for m in 0..x {
-            let mut tile = vec![];
-
-            for i in 0..TILE_SIZE {
-                for r in 0..TILE_SIZE {
-                    tile.push(img_rgb8.get_pixel(m * TILE_SIZE + i, n * TILE_SIZE + r))
-                }
-            }
+            let tile = tile_at(img, m, n);
             let index = uniq_tiles
                 .iter()
                 .position(|existing_tile| existing_tile == &tile);
 
             match index {
                 Some(index) => rows.push(index),
+                None if tileset.is_some() => panic!("tile ({m}, {n}) is not in the tileset"),
                 None => {
                     uniq_tiles.push(tile);
                     rows.push(uniq_tiles.len() - 1);
@@ -46,9 +63,1437 @@ pub fn generate_background_map(input: TokenStream) -> TokenStream {
         result.push(rows);
     }
 
+    result
+}
+
+// Paths in the macros are relative to the crate using them, wherever cargo
+// happens to run the compiler from.
+fn resolve(path: &str) -> PathBuf {
+    Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).join(path)
+}
+
+fn open(path: &str) -> RgbImage {
+    ImageReader::open(resolve(path))
+        .unwrap_or_else(|err| panic!("can't open {path}: {err}"))
+        .decode()
+        .unwrap()
+        .to_rgb8()
+}
+
+fn read(path: &str) -> String {
+    std::fs::read_to_string(resolve(path)).unwrap_or_else(|err| panic!("can't read {path}: {err}"))
+}
+
+// The lines of a data file, less blank ones and `#` comments.
+fn lines(source: &str) -> impl Iterator<Item = &str> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+#[proc_macro]
+pub fn generate_background_map(input: TokenStream) -> TokenStream {
+    let paths = Punctuated::<LitStr, Token![,]>::parse_terminated
+        .parse(input)
+        .unwrap();
+    let mut paths = paths.iter().map(LitStr::value);
+
+    let img_rgb8 = open(&paths.next().unwrap());
+    let tileset = paths.next().map(|path| open(&path));
+
+    let result = tile_indices(&img_rgb8, tileset.as_ref());
+
     let generated = quote! {
         [#([#(#result),*]),*]
     };
 
     generated.into()
 }
+
+// `generate_metatile_map!("map.png", "tiles.png", 2)` cuts the map into 2x2 (or
+// 3x3, ...) squares of tiles, keeps one metatile per distinct square and lays
+// the map out in those.
+#[proc_macro]
+pub fn generate_metatile_map(input: TokenStream) -> TokenStream {
+    let args = Punctuated::<Lit, Token![,]>::parse_terminated
+        .parse(input)
+        .unwrap();
+    let [Lit::Str(path), Lit::Str(tileset), Lit::Int(size)] = &args.iter().collect::<Vec<_>>()[..]
+    else {
+        panic!("expected a map path, a tileset path and a metatile size");
+    };
+    let size: usize = size.base10_parse().unwrap();
+
+    let tiles = tile_indices(&open(&path.value()), Some(&open(&tileset.value())));
+    if !tiles.len().is_multiple_of(size) || !tiles[0].len().is_multiple_of(size) {
+        panic!(
+            "{} isn't a whole number of {size}x{size} metatiles",
+            path.value()
+        );
+    }
+
+    let width = tiles[0].len() / size;
+    let height = tiles.len() / size;
+
+    let mut metatiles: Vec<Vec<usize>> = vec![];
+    let mut layout = vec![];
+    for n in 0..height {
+        for m in 0..width {
+            let metatile: Vec<usize> = (0..size * size)
+                .map(|i| tiles[n * size + i / size][m * size + i % size])
+                .collect();
+
+            let index = match metatiles.iter().position(|existing| existing == &metatile) {
+                Some(index) => index,
+                None => {
+                    metatiles.push(metatile);
+                    metatiles.len() - 1
+                }
+            };
+            layout.push(u8::try_from(index).expect("more than 256 distinct metatiles"));
+        }
+    }
+
+    let generated = quote! {
+        logic::metatile::MetatileMap {
+            size: #size,
+            width: #width,
+            metatiles: &[#(logic::metatile::Metatile::new(&[#(#metatiles),*])),*],
+            layout: &[#(#layout),*],
+        }
+    };
+
+    generated.into()
+}
+
+// `generate_tile_animations!("tiles.png", "strip.png", 12)` reads a strip with
+// one animated tile to a row. The first frame of a row has to be in the tileset
+// and says which tile animates, the frames then step every 12 frames in order.
+#[proc_macro]
+pub fn generate_tile_animations(input: TokenStream) -> TokenStream {
+    let args = Punctuated::<Lit, Token![,]>::parse_terminated
+        .parse(input)
+        .unwrap();
+    let [Lit::Str(tileset), Lit::Str(strip), Lit::Int(period)] =
+        &args.iter().collect::<Vec<_>>()[..]
+    else {
+        panic!("expected a tileset path, a strip path and a period in frames");
+    };
+    let period: u16 = period.base10_parse().unwrap();
+
+    let tileset = open(&tileset.value());
+    let strip = open(&strip.value());
+    let columns = strip.width() / TILE_SIZE;
+
+    let mut animations = vec![];
+    for n in 0..strip.height() / TILE_SIZE {
+        let first = tile_at(&strip, 0, n);
+        let tile = (0..tileset.height() / TILE_SIZE)
+            .flat_map(|y| (0..tileset.width() / TILE_SIZE).map(move |x| (x, y)))
+            .position(|(x, y)| tile_at(&tileset, x, y) == first)
+            .unwrap_or_else(|| panic!("the first frame of row {n} is not in the tileset"));
+        let frames = (0..columns).map(|m| (n * columns + m) as usize);
+
+        animations.push(quote! {
+            logic::animation::TileAnimation {
+                tile: #tile,
+                frames: &[#(#frames),*],
+                period: #period,
+            }
+        });
+    }
+
+    let generated = quote! {
+        [#(#animations),*]
+    };
+
+    generated.into()
+}
+
+// One object per line as `kind x y arguments`, in tiles:
+//
+//     sign x y text
+//     chest x y item flag          the flag remembers it's been opened
+//     item x y item flag           lies on the tile until picked up
+//     door x y map x y [condition] locked unless the condition holds
+//     warp x y map x y
+//
+// Items are named in lower case after crate::item::Item's variants.
+#[proc_macro]
+pub fn generate_map_objects(input: TokenStream) -> TokenStream {
+    let processed = parse_macro_input!(input as LitStr);
+
+    map_objects(&read(&processed.value())).into()
+}
+
+fn map_objects(source: &str) -> proc_macro2::TokenStream {
+    let mut objects = vec![];
+    for line in lines(source) {
+        let mut fields = line.split_whitespace().peekable();
+
+        let kind = fields.next().unwrap();
+        let x: i32 = number(fields.next(), line);
+        let y: i32 = number(fields.next(), line);
+
+        let kind = match kind {
+            "sign" => {
+                let text = fields.collect::<Vec<_>>().join(" ");
+                quote! { Sign(#text) }
+            }
+            "chest" | "item" => {
+                let item = item_name(fields.next().unwrap_or(""), line);
+                let flag = state_name(fields.next().unwrap_or(""), line);
+
+                if kind == "chest" {
+                    quote! { Chest(#item, #flag) }
+                } else {
+                    quote! { Item(#item, #flag) }
+                }
+            }
+            "door" | "warp" => {
+                let map = fields
+                    .next()
+                    .unwrap_or_else(|| panic!("no map in `{line}`"));
+                let spawn_x: i32 = number(fields.next(), line);
+                let spawn_y: i32 = number(fields.next(), line);
+                let warp = quote! {
+                    crate::map::Warp {
+                        map: #map,
+                        spawn: (#spawn_x, #spawn_y),
+                    }
+                };
+
+                if kind == "door" {
+                    let condition = condition(&mut fields, line);
+                    quote! { Door(#warp, #condition) }
+                } else {
+                    quote! { Warp(#warp) }
+                }
+            }
+            other => panic!("unknown map object kind `{other}` in `{line}`"),
+        };
+
+        objects.push(quote! {
+            crate::interaction::MapObject {
+                kind: crate::interaction::ObjectKind::#kind,
+                tile: (#x, #y),
+            }
+        });
+    }
+
+    quote! {
+        [#(#objects),*]
+    }
+}
+
+// One npc per line as `behaviour x y [arguments] text`, in world pixels and in
+// the order scripts number them:
+//
+//     still x y text
+//     wander x y radius text           strolls up to radius pixels from x y
+//     patrol x y x,y [x,y ...] text    walks from x y through each point and back
+#[proc_macro]
+pub fn generate_map_npcs(input: TokenStream) -> TokenStream {
+    let processed = parse_macro_input!(input as LitStr);
+
+    map_npcs(&read(&processed.value())).into()
+}
+
+fn map_npcs(source: &str) -> proc_macro2::TokenStream {
+    let mut npcs = vec![];
+    for line in lines(source) {
+        let mut fields = line.split_whitespace().peekable();
+
+        let behaviour = fields.next().unwrap();
+        let x: i32 = number(fields.next(), line);
+        let y: i32 = number(fields.next(), line);
+
+        let behaviour = match behaviour {
+            "still" => quote! { Still },
+            "wander" => {
+                let radius: i32 = number(fields.next(), line);
+                if radius < 0 {
+                    panic!("the radius can't be negative in `{line}`");
+                }
+                quote! { Wander { radius: #radius } }
+            }
+            "patrol" => {
+                let mut path = vec![quote! { (#x, #y) }];
+                while let Some(point) = fields.next_if(|field| field.contains(',')) {
+                    let (px, py) = point.split_once(',').unwrap();
+                    let (px, py): (i32, i32) = (number(Some(px), line), number(Some(py), line));
+                    path.push(quote! { (#px, #py) });
+                }
+                if path.len() < 2 {
+                    panic!("expected points as `x,y` after the start in `{line}`");
+                }
+                quote! { Patrol(&[#(#path),*]) }
+            }
+            other => panic!("unknown npc behaviour `{other}` in `{line}`"),
+        };
+
+        let text = fields.collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            panic!("no text in `{line}`");
+        }
+
+        npcs.push(quote! {
+            ((#x, #y), crate::npc::Behaviour::#behaviour, #text)
+        });
+    }
+
+    quote! {
+        [#(#npcs),*]
+    }
+}
+
+// Each line of the legend is `tiles terrain [footstep] [encounters]`, with
+// `tiles` a tile index or a range like `1-4`. Unlisted tiles are walls.
+#[proc_macro]
+pub fn generate_tile_attributes(input: TokenStream) -> TokenStream {
+    let processed = parse_macro_input!(input as LitStr);
+
+    tile_attributes(&read(&processed.value())).into()
+}
+
+fn tile_attributes(source: &str) -> proc_macro2::TokenStream {
+    let mut attributes = vec![];
+    for line in lines(source) {
+        let mut fields = line.split_whitespace();
+
+        let tiles = fields.next().unwrap();
+        let (first, last) = tiles.split_once('-').unwrap_or((tiles, tiles));
+        let parse = |tile: &str| -> usize {
+            tile.parse()
+                .unwrap_or_else(|_| panic!("bad tile `{tile}` in `{line}`"))
+        };
+
+        let terrain = match fields.next() {
+            Some("wall") => quote! { Wall },
+            Some("grass") => quote! { Grass },
+            Some("tall-grass") => quote! { TallGrass },
+            Some("water") => quote! { Water },
+            Some("ledge") => quote! { Ledge },
+            Some("ice") => quote! { Ice },
+            other => panic!("unknown terrain {other:?} in `{line}`"),
+        };
+        let footstep = match fields.next() {
+            None | Some("none") => quote! { None },
+            Some("soft") => quote! { Soft },
+            Some("hard") => quote! { Hard },
+            Some("splash") => quote! { Splash },
+            Some(other) => panic!("unknown footstep `{other}` in `{line}`"),
+        };
+        let encounters = match fields.next() {
+            None => false,
+            Some("encounters") => true,
+            Some(other) => panic!("expected `encounters` but found `{other}` in `{line}`"),
+        };
+
+        let (first, last) = (parse(first), parse(last));
+        if first > last {
+            panic!("tiles `{tiles}` run backwards in `{line}`");
+        }
+
+        for tile in first..=last {
+            if attributes.len() <= tile {
+                attributes.resize(tile + 1, None);
+            }
+            attributes[tile] = Some(quote! {
+                crate::terrain::Attributes {
+                    terrain: crate::terrain::Terrain::#terrain,
+                    footstep: crate::terrain::Footstep::#footstep,
+                    encounters: #encounters,
+                }
+            });
+        }
+    }
+
+    let attributes = attributes.into_iter().map(|attributes| {
+        attributes.unwrap_or_else(|| quote! { crate::terrain::Attributes::WALL })
+    });
+
+    quote! {
+        [#(#attributes),*]
+    }
+}
+
+// Trigger zones, one per line as `when x y width height chance [condition] event
+// arguments` in tiles, where `when` is enter, exit or step and `chance` is a
+// percentage. Conditions are described on `condition`.
+#[proc_macro]
+pub fn generate_map_triggers(input: TokenStream) -> TokenStream {
+    let processed = parse_macro_input!(input as LitStr);
+
+    map_triggers(&read(&processed.value())).into()
+}
+
+fn map_triggers(source: &str) -> proc_macro2::TokenStream {
+    let mut zones = vec![];
+    for line in lines(source) {
+        let mut fields = line.split_whitespace().peekable();
+
+        let when = match fields.next().unwrap() {
+            "enter" => quote! { Enter },
+            "exit" => quote! { Exit },
+            "step" => quote! { Step },
+            other => panic!("unknown trigger `{other}` in `{line}`"),
+        };
+
+        let mut next = || -> i32 { number(fields.next(), line) };
+        let (x, y, width, height) = (next(), next(), next(), next());
+        let chance = u8::try_from(next())
+            .ok()
+            .filter(|&chance| chance <= 100)
+            .unwrap_or_else(|| panic!("chance should be 0 to 100 in `{line}`"));
+
+        let condition = condition(&mut fields, line);
+        let event = fields
+            .next()
+            .unwrap_or_else(|| panic!("no event in `{line}`"));
+        let argument = fields.collect::<Vec<_>>().join(" ");
+        let event = match event {
+            "cutscene" => quote! { Cutscene(#argument) },
+            "area" => quote! { AreaName(#argument) },
+            "encounter" => quote! { Encounter(#argument) },
+            other => panic!("unknown trigger event `{other}` in `{line}`"),
+        };
+
+        zones.push(quote! {
+            logic::trigger::Zone {
+                area: (#x, #y, #width, #height),
+                when: logic::trigger::When::#when,
+                chance: #chance,
+                condition: #condition,
+                event: crate::interaction::Trigger::#event,
+            }
+        });
+    }
+
+    quote! {
+        [#(#zones),*]
+    }
+}
+
+// Names for the story flags and counters in logic::state, one per line as
+// `flag name` or `var name`. Each becomes a const in SCREAMING_SNAKE_CASE, which
+// the other macros refer to as crate::state::NAME.
+#[proc_macro]
+pub fn generate_state_names(input: TokenStream) -> TokenStream {
+    let processed = parse_macro_input!(input as LitStr);
+
+    state_names(&read(&processed.value())).into()
+}
+
+fn state_names(source: &str) -> proc_macro2::TokenStream {
+    let (mut flags, mut vars) = (0u8, 0u8);
+    let mut seen = vec![];
+    let mut names = vec![];
+    for line in lines(source) {
+        let Some((kind, name)) = line.split_once(' ') else {
+            panic!("expected `flag name` or `var name` in `{line}`");
+        };
+        let name = name.trim();
+        if seen.contains(&name) {
+            panic!("`{name}` is named twice");
+        }
+        seen.push(name);
+        let ident = state_ident(name, line);
+
+        let count = match kind {
+            "flag" => &mut flags,
+            "var" => &mut vars,
+            other => panic!("unknown kind `{other}` in `{line}`"),
+        };
+        let index = *count;
+        *count = count
+            .checked_add(1)
+            .unwrap_or_else(|| panic!("too many of `{kind}` at `{line}`"));
+
+        names.push(if kind == "flag" {
+            quote! { pub const #ident: logic::state::Flag = logic::state::Flag(#index); }
+        } else {
+            quote! { pub const #ident: logic::state::Var = logic::state::Var(#index); }
+        });
+    }
+
+    quote! {
+        #(#names)*
+
+        const _: () = assert!(
+            #flags as usize <= logic::state::FLAGS && #vars as usize <= logic::state::VARS,
+            "too many flags or vars for logic::state"
+        );
+    }
+}
+
+fn number<T: std::str::FromStr>(field: Option<&str>, line: &str) -> T {
+    let field = field.unwrap_or_else(|| panic!("too few fields in `{line}`"));
+    field
+        .parse()
+        .unwrap_or_else(|_| panic!("bad number `{field}` in `{line}`"))
+}
+
+fn item_name(name: &str, line: &str) -> proc_macro2::TokenStream {
+    let mut chars = name.chars();
+    let Some(first) = chars.next().filter(char::is_ascii_lowercase) else {
+        panic!("bad item name `{name}` in `{line}`");
+    };
+
+    let ident = format_ident!("{}{}", first.to_ascii_uppercase(), chars.as_str());
+    quote! { crate::item::Item::#ident }
+}
+
+fn state_name(name: &str, line: &str) -> proc_macro2::TokenStream {
+    let ident = state_ident(name, line);
+    quote! { crate::state::#ident }
+}
+
+// Names are lower case words joined by `-`, and the consts are the same in
+// SCREAMING_SNAKE_CASE.
+fn state_ident(name: &str, line: &str) -> Ident {
+    if !name.starts_with(|c: char| c.is_ascii_lowercase())
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        panic!("bad flag or var name `{name}` in `{line}`");
+    }
+
+    format_ident!("{}", name.to_uppercase().replace('-', "_"))
+}
+
+// An optional condition on the state, one of `if flag`, `unless flag`,
+// `if var >= n` or `if var < n`. Without one it's always true.
+fn condition<'a>(
+    fields: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>,
+    line: &str,
+) -> proc_macro2::TokenStream {
+    let negated = match fields.peek() {
+        Some(&"if") => false,
+        Some(&"unless") => true,
+        _ => return quote! { logic::state::Condition::Always },
+    };
+    fields.next();
+
+    let name = state_name(fields.next().unwrap_or(""), line);
+    let comparison = match fields.peek() {
+        Some(&">=") => quote! { AtLeast },
+        Some(&"<") => quote! { Below },
+        _ if negated => return quote! { logic::state::Condition::Unset(#name) },
+        _ => return quote! { logic::state::Condition::Set(#name) },
+    };
+    fields.next();
+
+    if negated {
+        panic!("comparisons only go with `if` in `{line}`");
+    }
+    let value: u8 = fields
+        .next()
+        .and_then(|field| field.parse().ok())
+        .unwrap_or_else(|| panic!("expected a number from 0 to 255 in `{line}`"));
+
+    quote! { logic::state::Condition::#comparison(#name, #value) }
+}
+
+// Cutscenes, each a `script name` line, its commands one to a line and `end`.
+// Actors are `player` or `npc 0` for the map's first npc, positions are tiles.
+//
+//     move actor x y [x y ...]    face actor up|down|left|right
+//     say text                    wait frames
+//     set flag                    clear flag
+//     add var amount              fade out|in frames
+//     warp map x y
+//
+// A condition line (see `condition`) runs the commands up to its own `end` only
+// when it holds.
+#[proc_macro]
+pub fn generate_scripts(input: TokenStream) -> TokenStream {
+    let processed = parse_macro_input!(input as LitStr);
+
+    scripts(&read(&processed.value())).into()
+}
+
+fn scripts(source: &str) -> proc_macro2::TokenStream {
+    let mut scripts = vec![];
+    let mut current: Option<(String, Vec<proc_macro2::TokenStream>)> = None;
+    // Where each unfinished `if` is in the commands, with its condition.
+    let mut blocks: Vec<(usize, proc_macro2::TokenStream)> = vec![];
+    for line in lines(source) {
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match (word, current.as_mut()) {
+            ("script", None) => current = Some((rest.to_string(), vec![])),
+            ("if" | "unless", Some((_, commands))) => {
+                let mut fields = line.split_whitespace().peekable();
+                blocks.push((commands.len(), condition(&mut fields, line)));
+                if fields.next().is_some() {
+                    panic!("expected a line break after the condition in `{line}`");
+                }
+                commands.push(quote! {});
+            }
+            ("end", Some((_, commands))) if !blocks.is_empty() => {
+                let (start, condition) = blocks.pop().unwrap();
+                let skip = commands.len() - start - 1;
+                commands[start] = quote! { logic::script::Command::If(#condition, #skip) };
+            }
+            ("end", Some(_)) => {
+                let (name, commands) = current.take().unwrap();
+                scripts.push(quote! {
+                    logic::script::Script {
+                        name: #name,
+                        commands: &[#(#commands),*],
+                    }
+                });
+            }
+            ("script" | "end", _) => panic!("unexpected `{line}`"),
+            (_, None) => panic!("`{line}` is outside a script"),
+            (_, Some((_, commands))) => commands.push(script_command(word, rest, line)),
+        }
+    }
+
+    if let Some((name, _)) = current {
+        panic!("script `{name}` has no `end`");
+    }
+
+    quote! {
+        [#(#scripts),*]
+    }
+}
+
+fn script_command(word: &str, rest: &str, line: &str) -> proc_macro2::TokenStream {
+    let mut fields = rest
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|field| !field.is_empty());
+
+    let int = |field: Option<&str>| -> i32 { number(field, line) };
+    let frames = |field: Option<&str>| {
+        u16::try_from(int(field)).unwrap_or_else(|_| panic!("bad frame count in `{line}`"))
+    };
+    let actor = |fields: &mut dyn Iterator<Item = &str>| match fields.next() {
+        Some("player") => quote! { logic::script::Actor::Player },
+        Some("npc") => {
+            let index = usize::try_from(int(fields.next()))
+                .unwrap_or_else(|_| panic!("bad npc index in `{line}`"));
+            quote! { logic::script::Actor::Npc(#index) }
+        }
+        _ => panic!("expected `player` or `npc n` in `{line}`"),
+    };
+
+    match word {
+        "move" => {
+            let actor = actor(&mut fields);
+            let numbers: Vec<i32> = fields.map(|field| int(Some(field))).collect();
+            if numbers.is_empty() || !numbers.len().is_multiple_of(2) {
+                panic!("expected pairs of tile coordinates in `{line}`");
+            }
+            let path = numbers.chunks(2).map(|pair| {
+                let (x, y) = (pair[0], pair[1]);
+                quote! { (#x, #y) }
+            });
+            quote! { logic::script::Command::Move(#actor, &[#(#path),*]) }
+        }
+        "face" => {
+            let actor = actor(&mut fields);
+            let facing = match fields.next() {
+                Some("up") => quote! { Up },
+                Some("down") => quote! { Down },
+                Some("left") => quote! { Left },
+                Some("right") => quote! { Right },
+                _ => panic!("expected up, down, left or right in `{line}`"),
+            };
+            quote! { logic::script::Command::Face(#actor, logic::movement::MovementType::#facing) }
+        }
+        "say" => quote! { logic::script::Command::Say(#rest) },
+        "wait" => {
+            let frames = frames(fields.next());
+            quote! { logic::script::Command::Wait(#frames) }
+        }
+        "set" => {
+            let flag = state_name(rest, line);
+            quote! { logic::script::Command::Set(#flag) }
+        }
+        "clear" => {
+            let flag = state_name(rest, line);
+            quote! { logic::script::Command::Clear(#flag) }
+        }
+        "add" => {
+            let var = state_name(fields.next().unwrap_or(""), line);
+            let amount = i16::try_from(int(fields.next()))
+                .unwrap_or_else(|_| panic!("bad amount in `{line}`"));
+            quote! { logic::script::Command::Add(#var, #amount) }
+        }
+        "fade" => {
+            let fade = match fields.next() {
+                Some("out") => quote! { Out },
+                Some("in") => quote! { In },
+                _ => panic!("expected `out` or `in` in `{line}`"),
+            };
+            let frames = frames(fields.next());
+            quote! { logic::script::Command::Fade(logic::script::Fade::#fade, #frames) }
+        }
+        "warp" => {
+            let map = fields
+                .next()
+                .unwrap_or_else(|| panic!("no map in `{line}`"));
+            let (x, y) = (int(fields.next()), int(fields.next()));
+            quote! { logic::script::Command::Warp(#map, (#x, #y)) }
+        }
+        other => panic!("unknown script command `{other}` in `{line}`"),
+    }
+}
+
+// Each line is `tag x y footprint_x footprint_y footprint_width footprint_height`,
+// with the position in world pixels and the footprint relative to it.
+#[proc_macro]
+pub fn generate_map_props(input: TokenStream) -> TokenStream {
+    let processed = parse_macro_input!(input as LitStr);
+
+    map_props(&read(&processed.value())).into()
+}
+
+fn map_props(source: &str) -> proc_macro2::TokenStream {
+    let mut props = vec![];
+    for line in lines(source) {
+        let mut fields = line.split_whitespace();
+
+        let tag = fields.next().unwrap();
+        let numbers: Vec<i32> = fields.map(|field| number(Some(field), line)).collect();
+
+        let [x, y, fx, fy, fw, fh] = numbers[..] else {
+            panic!("expected a tag and 6 numbers in `{line}`");
+        };
+        if fw < 0 || fh < 0 {
+            panic!("the footprint can't have a negative size in `{line}`");
+        }
+
+        props.push(quote! {
+            crate::prop::Prop {
+                tag: #tag,
+                position: (#x, #y),
+                footprint: (#fx, #fy, #fw, #fh),
+            }
+        });
+    }
+
+    quote! {
+        [#(#props),*]
+    }
+}
+
+struct Gfx {
+    module: Ident,
+    images: Vec<(Ident, bool, LitStr)>,
+}
+
+impl Parse for Gfx {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let module = input.parse()?;
+        let mut images = vec![];
+
+        while input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let name = input.parse()?;
+            input.parse::<Token![=>]>()?;
+
+            let deduplicate = input.peek(Ident);
+            if deduplicate {
+                let keyword: Ident = input.parse()?;
+                if keyword != "deduplicate" {
+                    return Err(syn::Error::new(keyword.span(), "expected `deduplicate`"));
+                }
+            }
+
+            images.push((name, deduplicate, input.parse()?));
+        }
+
+        Ok(Self { module, images })
+    }
+}
+
+// Like agb's include_background_gfx but for 16 colour backgrounds, with the
+// colours of all the images quantised together into up to 16 palette banks:
+//
+// include_quantised_gfx!(gfx, tiles => "tiles.png", house => deduplicate "house.png")
+//
+// Each tile's bank is in its TileSetting. Paths can start with $OUT_DIR.
+#[proc_macro]
+pub fn include_quantised_gfx(input: TokenStream) -> TokenStream {
+    let Gfx { module, images } = parse_macro_input!(input as Gfx);
+
+    let mut pixels = vec![];
+    let mut ranges = vec![];
+    let mut paths = vec![];
+    for (_, _, path) in &images {
+        let path = match path.value().strip_prefix("$OUT_DIR") {
+            Some(rest) => std::env::var("OUT_DIR").unwrap() + rest,
+            None => path.value(),
+        };
+        let img = ImageReader::open(resolve(&path))
+            .unwrap_or_else(|err| panic!("can't open {path}: {err}"))
+            .decode()
+            .unwrap()
+            .to_rgba8();
+        if !img.width().is_multiple_of(TILE_SIZE) || !img.height().is_multiple_of(TILE_SIZE) {
+            panic!("{path} isn't a whole number of tiles");
+        }
+
+        let start = pixels.len();
+        for n in 0..img.height() / TILE_SIZE {
+            for m in 0..img.width() / TILE_SIZE {
+                let mut tile = [None; 64];
+                for (i, pixel) in tile.iter_mut().enumerate() {
+                    let [r, g, b, a] = img
+                        .get_pixel(
+                            m * TILE_SIZE + i as u32 % TILE_SIZE,
+                            n * TILE_SIZE + i as u32 / TILE_SIZE,
+                        )
+                        .0;
+                    if a >= 128 {
+                        *pixel = Some(quantise::rgb15(r, g, b));
+                    }
+                }
+                pixels.push(tile);
+            }
+        }
+        ranges.push(start..pixels.len());
+        paths.push(
+            std::fs::canonicalize(resolve(&path))
+                .unwrap()
+                .display()
+                .to_string(),
+        );
+    }
+
+    let quantised = quantise::quantise(&pixels);
+
+    let palettes = quantised.palettes.iter().map(|colours| {
+        quote! { agb::display::palette16::Palette16::new([#(#colours),*]) }
+    });
+
+    let gfx =
+        images
+            .iter()
+            .zip(ranges)
+            .zip(&paths)
+            .map(|(((name, deduplicate, _), range), path)| {
+                let mut data = vec![];
+                let mut kept: Vec<&quantise::Tile> = vec![];
+                let mut settings = vec![];
+
+                for tile in &quantised.tiles[range] {
+                    let existing = kept.iter().position(|other| {
+                        *deduplicate && other.pixels == tile.pixels && other.bank == tile.bank
+                    });
+                    let index = existing.unwrap_or_else(|| {
+                        data.extend(tile.pixels.chunks(2).map(|pair| pair[0] | (pair[1] << 4)));
+                        kept.push(tile);
+                        kept.len() - 1
+                    }) as u16;
+                    let bank = tile.bank;
+
+                    settings.push(quote! {
+                        agb::display::tiled::TileSetting::new(#index, false, false, #bank)
+                    });
+                }
+
+                let data = Literal::byte_string(&data);
+
+                quote! {
+                    #[allow(non_upper_case_globals)]
+                    pub static #name: agb::display::tile_data::TileData = {
+                        const _: &[u8] = include_bytes!(#path);
+
+                        const TILE_DATA: &[u8] = {
+                            pub struct AlignedAs<Align, Bytes: ?Sized> {
+                                pub _align: [Align; 0],
+                                pub bytes: Bytes,
+                            }
+
+                            const ALIGNED: &AlignedAs<u32, [u8]> = &AlignedAs {
+                                _align: [],
+                                bytes: *#data,
+                            };
+
+                            &ALIGNED.bytes
+                        };
+
+                        agb::display::tile_data::TileData::new(
+                            agb::display::tiled::TileSet::new(
+                                TILE_DATA,
+                                agb::display::tiled::TileFormat::FourBpp,
+                            ),
+                            &[#(#settings),*],
+                        )
+                    };
+                }
+            });
+
+    let generated = quote! {
+        mod #module {
+            pub static PALETTES: &[agb::display::palette16::Palette16] = &[#(#palettes),*];
+
+            #(#gfx)*
+        }
+    };
+
+    generated.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_tokens(generated: proc_macro2::TokenStream, expected: proc_macro2::TokenStream) {
+        assert_eq!(generated.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn objects_parse() {
+        let source = "
+            # the house
+            sign 1 2 Home  sweet home
+
+            item 3 4 key got-key
+            door 5 -6 house-1 7 8 if got-key
+            warp 0 0 bg 9 10
+        ";
+
+        assert_tokens(
+            map_objects(source),
+            quote! {[
+                crate::interaction::MapObject {
+                    kind: crate::interaction::ObjectKind::Sign("Home sweet home"),
+                    tile: (1i32, 2i32),
+                },
+                crate::interaction::MapObject {
+                    kind: crate::interaction::ObjectKind::Item(
+                        crate::item::Item::Key,
+                        crate::state::GOT_KEY
+                    ),
+                    tile: (3i32, 4i32),
+                },
+                crate::interaction::MapObject {
+                    kind: crate::interaction::ObjectKind::Door(
+                        crate::map::Warp { map: "house-1", spawn: (7i32, 8i32), },
+                        logic::state::Condition::Set(crate::state::GOT_KEY)
+                    ),
+                    tile: (5i32, -6i32),
+                },
+                crate::interaction::MapObject {
+                    kind: crate::interaction::ObjectKind::Warp(
+                        crate::map::Warp { map: "bg", spawn: (9i32, 10i32), }
+                    ),
+                    tile: (0i32, 0i32),
+                }
+            ]},
+        );
+    }
+
+    #[test]
+    fn doors_without_a_condition_are_always_open() {
+        let generated = map_objects("door 1 1 bg 2 2").to_string();
+
+        assert!(generated.contains(&quote! { logic::state::Condition::Always }.to_string()));
+    }
+
+    #[test]
+    fn empty_object_files_have_no_objects() {
+        assert_tokens(map_objects("\n  # nothing here\n"), quote! { [] });
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown map object kind `chair` in `chair 1 1`")]
+    fn unknown_objects_dont_parse() {
+        map_objects("chair 1 1");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad number `x` in `sign x 1 hi`")]
+    fn object_positions_are_numbers() {
+        map_objects("sign x 1 hi");
+    }
+
+    #[test]
+    #[should_panic(expected = "too few fields in `warp 1 1 bg 2`")]
+    fn warps_need_a_spawn() {
+        map_objects("warp 1 1 bg 2");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad item name `Key`")]
+    fn items_are_lower_case() {
+        map_objects("chest 1 1 Key got-key");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad flag or var name ``")]
+    fn chests_need_a_flag() {
+        map_objects("chest 1 1 key");
+    }
+
+    #[test]
+    fn legends_parse() {
+        let source = "
+            # water, then grass either side of it
+            1 grass soft encounters
+            3-4 water splash
+        ";
+
+        assert_tokens(
+            tile_attributes(source),
+            quote! {[
+                crate::terrain::Attributes::WALL,
+                crate::terrain::Attributes {
+                    terrain: crate::terrain::Terrain::Grass,
+                    footstep: crate::terrain::Footstep::Soft,
+                    encounters: true,
+                },
+                crate::terrain::Attributes::WALL,
+                crate::terrain::Attributes {
+                    terrain: crate::terrain::Terrain::Water,
+                    footstep: crate::terrain::Footstep::Splash,
+                    encounters: false,
+                },
+                crate::terrain::Attributes {
+                    terrain: crate::terrain::Terrain::Water,
+                    footstep: crate::terrain::Footstep::Splash,
+                    encounters: false,
+                }
+            ]},
+        );
+    }
+
+    #[test]
+    fn later_legend_lines_win() {
+        assert_tokens(
+            tile_attributes("0-1 ice\n1 ledge hard"),
+            quote! {[
+                crate::terrain::Attributes {
+                    terrain: crate::terrain::Terrain::Ice,
+                    footstep: crate::terrain::Footstep::None,
+                    encounters: false,
+                },
+                crate::terrain::Attributes {
+                    terrain: crate::terrain::Terrain::Ledge,
+                    footstep: crate::terrain::Footstep::Hard,
+                    encounters: false,
+                }
+            ]},
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown terrain Some(\"lava\") in `2 lava`")]
+    fn unknown_terrains_dont_parse() {
+        tile_attributes("2 lava");
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown terrain None in `2`")]
+    fn legend_lines_need_a_terrain() {
+        tile_attributes("2");
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown footstep `squelch`")]
+    fn unknown_footsteps_dont_parse() {
+        tile_attributes("2 grass squelch");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected `encounters` but found `monsters`")]
+    fn only_encounters_follow_the_footstep() {
+        tile_attributes("2 grass soft monsters");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad tile `a`")]
+    fn legend_tiles_are_numbers() {
+        tile_attributes("1-a grass");
+    }
+
+    #[test]
+    #[should_panic(expected = "tiles `4-1` run backwards")]
+    fn legend_ranges_run_forwards() {
+        tile_attributes("4-1 grass");
+    }
+
+    #[test]
+    fn triggers_parse() {
+        let source = "
+            enter 10 10 12 3 100 unless visited-home cutscene home
+            step 0 -1 4 4 15 encounter slime
+            exit 1 2 3 4 0 if slimes-met >= 2 area  Home town
+        ";
+
+        assert_tokens(
+            map_triggers(source),
+            quote! {[
+                logic::trigger::Zone {
+                    area: (10i32, 10i32, 12i32, 3i32),
+                    when: logic::trigger::When::Enter,
+                    chance: 100u8,
+                    condition: logic::state::Condition::Unset(crate::state::VISITED_HOME),
+                    event: crate::interaction::Trigger::Cutscene("home"),
+                },
+                logic::trigger::Zone {
+                    area: (0i32, -1i32, 4i32, 4i32),
+                    when: logic::trigger::When::Step,
+                    chance: 15u8,
+                    condition: logic::state::Condition::Always,
+                    event: crate::interaction::Trigger::Encounter("slime"),
+                },
+                logic::trigger::Zone {
+                    area: (1i32, 2i32, 3i32, 4i32),
+                    when: logic::trigger::When::Exit,
+                    chance: 0u8,
+                    condition: logic::state::Condition::AtLeast(crate::state::SLIMES_MET, 2u8),
+                    event: crate::interaction::Trigger::AreaName("Home town"),
+                }
+            ]},
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown trigger `leave`")]
+    fn unknown_triggers_dont_parse() {
+        map_triggers("leave 0 0 1 1 100 area Home");
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown trigger event `music`")]
+    fn unknown_trigger_events_dont_parse() {
+        map_triggers("enter 0 0 1 1 100 music town");
+    }
+
+    #[test]
+    #[should_panic(expected = "chance should be 0 to 100")]
+    fn trigger_chances_are_percentages() {
+        map_triggers("enter 0 0 1 1 101 area Home");
+    }
+
+    #[test]
+    #[should_panic(expected = "too few fields")]
+    fn triggers_need_a_whole_area() {
+        map_triggers("enter 0 0 1");
+    }
+
+    #[test]
+    #[should_panic(expected = "no event in `enter 0 0 1 1 100 if got-key`")]
+    fn triggers_need_an_event() {
+        map_triggers("enter 0 0 1 1 100 if got-key");
+    }
+
+    #[test]
+    fn state_names_parse() {
+        let source = "
+            flag visited-home
+            var slimes-met
+            flag got-key2
+        ";
+
+        assert_tokens(
+            state_names(source),
+            quote! {
+                pub const VISITED_HOME: logic::state::Flag = logic::state::Flag(0u8);
+                pub const SLIMES_MET: logic::state::Var = logic::state::Var(0u8);
+                pub const GOT_KEY2: logic::state::Flag = logic::state::Flag(1u8);
+
+                const _: () = assert!(
+                    2u8 as usize <= logic::state::FLAGS && 1u8 as usize <= logic::state::VARS,
+                    "too many flags or vars for logic::state"
+                );
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown kind `switch`")]
+    fn unknown_state_kinds_dont_parse() {
+        state_names("switch lights-on");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected `flag name` or `var name` in `flag`")]
+    fn state_names_need_a_name() {
+        state_names("flag");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad flag or var name `Got_Key`")]
+    fn state_names_are_kebab_case() {
+        state_names("flag Got_Key");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad flag or var name `2nd-visit`")]
+    fn state_names_start_with_a_letter() {
+        state_names("flag 2nd-visit");
+    }
+
+    #[test]
+    #[should_panic(expected = "`got-key` is named twice")]
+    fn state_names_are_unique() {
+        state_names("flag got-key\nvar got-key");
+    }
+
+    #[test]
+    #[should_panic(expected = "too many of `flag`")]
+    fn state_indices_fit_a_byte() {
+        let source: String = (0..=256).map(|i| format!("flag f{i}\n")).collect();
+        state_names(&source);
+    }
+
+    #[test]
+    #[should_panic(expected = "comparisons only go with `if`")]
+    fn unless_doesnt_compare() {
+        map_triggers("enter 0 0 1 1 100 unless slimes-met < 2 area Home");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a number from 0 to 255")]
+    fn comparisons_are_against_a_byte() {
+        map_triggers("enter 0 0 1 1 100 if slimes-met < 256 area Home");
+    }
+
+    #[test]
+    fn scripts_parse() {
+        let source = "
+            script home
+                fade out 20
+                move player 1 2, 3 4
+                if got-key
+                    say Welcome  back!
+                    unless visited-home
+                        face npc 0 left
+                    end
+                end
+                add slimes-met -1
+                set visited-home
+            end
+
+            script away
+                warp bg 5 6
+                wait 30
+                clear got-key
+            end
+        ";
+
+        assert_tokens(
+            scripts(source),
+            quote! {[
+                logic::script::Script {
+                    name: "home",
+                    commands: &[
+                        logic::script::Command::Fade(logic::script::Fade::Out, 20u16),
+                        logic::script::Command::Move(
+                            logic::script::Actor::Player,
+                            &[(1i32, 2i32), (3i32, 4i32)]
+                        ),
+                        logic::script::Command::If(
+                            logic::state::Condition::Set(crate::state::GOT_KEY),
+                            3usize
+                        ),
+                        logic::script::Command::Say("Welcome  back!"),
+                        logic::script::Command::If(
+                            logic::state::Condition::Unset(crate::state::VISITED_HOME),
+                            1usize
+                        ),
+                        logic::script::Command::Face(
+                            logic::script::Actor::Npc(0usize),
+                            logic::movement::MovementType::Left
+                        ),
+                        logic::script::Command::Add(crate::state::SLIMES_MET, -1i16),
+                        logic::script::Command::Set(crate::state::VISITED_HOME)
+                    ],
+                },
+                logic::script::Script {
+                    name: "away",
+                    commands: &[
+                        logic::script::Command::Warp("bg", (5i32, 6i32)),
+                        logic::script::Command::Wait(30u16),
+                        logic::script::Command::Clear(crate::state::GOT_KEY)
+                    ],
+                }
+            ]},
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown script command `dance`")]
+    fn unknown_commands_dont_parse() {
+        scripts("script a\ndance player\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "script `a` has no `end`")]
+    fn scripts_need_an_end() {
+        scripts("script a\nwait 1");
+    }
+
+    #[test]
+    #[should_panic(expected = "script `a` has no `end`")]
+    fn conditions_need_their_own_end() {
+        scripts("script a\nif got-key\nwait 1\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "`wait 1` is outside a script")]
+    fn commands_go_in_scripts() {
+        scripts("wait 1");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected `script b`")]
+    fn scripts_dont_nest() {
+        scripts("script a\nscript b\nend\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a line break after the condition")]
+    fn conditions_go_on_their_own_line() {
+        scripts("script a\nif got-key wait 1\nend\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected pairs of tile coordinates")]
+    fn moves_go_to_whole_tiles() {
+        scripts("script a\nmove player 1 2 3\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected `player` or `npc n`")]
+    fn actors_are_the_player_or_an_npc() {
+        scripts("script a\nface dog up\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad npc index")]
+    fn npc_indices_arent_negative() {
+        scripts("script a\nface npc -1 up\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected up, down, left or right")]
+    fn facings_are_directions() {
+        scripts("script a\nface player north\nend");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad frame count")]
+    fn frame_counts_arent_negative() {
+        scripts("script a\nfade in -5\nend");
+    }
+
+    #[test]
+    fn props_parse() {
+        let source = "
+            # tag      x    y    footprint
+            House-1   64  144    4 24 56 40
+            Tree     192  -8     4 24  8  0
+        ";
+
+        assert_tokens(
+            map_props(source),
+            quote! {[
+                crate::prop::Prop {
+                    tag: "House-1",
+                    position: (64i32, 144i32),
+                    footprint: (4i32, 24i32, 56i32, 40i32),
+                },
+                crate::prop::Prop {
+                    tag: "Tree",
+                    position: (192i32, -8i32),
+                    footprint: (4i32, 24i32, 8i32, 0i32),
+                }
+            ]},
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a tag and 6 numbers in `Tree 1 2 3 4 5`")]
+    fn props_need_a_whole_footprint() {
+        map_props("Tree 1 2 3 4 5");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a tag and 6 numbers")]
+    fn props_have_nothing_after_the_footprint() {
+        map_props("Tree 1 2 3 4 5 6 7");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad number `4.5`")]
+    fn prop_positions_are_whole_pixels() {
+        map_props("Tree 1 2 3 4.5 5 6");
+    }
+
+    #[test]
+    #[should_panic(expected = "the footprint can't have a negative size")]
+    fn footprints_arent_negative() {
+        map_props("Tree 1 2 3 4 -5 6");
+    }
+
+    #[test]
+    fn npcs_parse() {
+        let source = "
+            wander  48 136  32                          Nice weather today!
+            patrol 152  40  152,152 176,152 176,40      I'm on my rounds.
+            still   64  48                              2 cats live here.
+        ";
+
+        assert_tokens(
+            map_npcs(source),
+            quote! {[
+                (
+                    (48i32, 136i32),
+                    crate::npc::Behaviour::Wander { radius: 32i32 },
+                    "Nice weather today!"
+                ),
+                (
+                    (152i32, 40i32),
+                    crate::npc::Behaviour::Patrol(&[
+                        (152i32, 40i32),
+                        (152i32, 152i32),
+                        (176i32, 152i32),
+                        (176i32, 40i32)
+                    ]),
+                    "I'm on my rounds."
+                ),
+                ((64i32, 48i32), crate::npc::Behaviour::Still, "2 cats live here.")
+            ]},
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown npc behaviour `dance`")]
+    fn unknown_behaviours_dont_parse() {
+        map_npcs("dance 1 2 Hi.");
+    }
+
+    #[test]
+    #[should_panic(expected = "no text in `still 1 2`")]
+    fn npcs_have_something_to_say() {
+        map_npcs("still 1 2");
+    }
+
+    #[test]
+    #[should_panic(expected = "the radius can't be negative")]
+    fn wander_radii_arent_negative() {
+        map_npcs("wander 1 2 -3 Hi.");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected points as `x,y` after the start")]
+    fn patrols_go_somewhere() {
+        map_npcs("patrol 1 2 Hi.");
+    }
+
+    #[test]
+    #[should_panic(expected = "bad number `b`")]
+    fn patrol_points_are_numbers() {
+        map_npcs("patrol 1 2 3,b Hi.");
+    }
+}
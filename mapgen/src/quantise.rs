@@ -0,0 +1,324 @@
+// Squeezes tiles of arbitrary colours into the GBA's 16 background palette banks
+// of 16 colours, where colour 0 of every bank is transparent.
+
+pub const BANKS: usize = 16;
+pub const BANK_COLOURS: usize = 15;
+
+// A tile as 64 pixels in reading order, each a 15-bit GBA colour or None where
+// it's see-through.
+pub type Pixels = [Option<u16>; 64];
+
+pub struct Tile {
+    // Indices into the bank, 0 for transparent.
+    pub pixels: [u8; 64],
+    pub bank: u8,
+}
+
+pub struct Quantised {
+    pub palettes: Vec<[u16; 16]>,
+    pub tiles: Vec<Tile>,
+}
+
+pub fn rgb15(r: u8, g: u8, b: u8) -> u16 {
+    (r as u16 >> 3) | ((g as u16 >> 3) << 5) | ((b as u16 >> 3) << 10)
+}
+
+fn channels(colour: u16) -> [i32; 3] {
+    [
+        (colour & 31) as i32,
+        ((colour >> 5) & 31) as i32,
+        ((colour >> 10) & 31) as i32,
+    ]
+}
+
+fn distance(a: u16, b: u16) -> i32 {
+    let (a, b) = (channels(a), channels(b));
+
+    (0..3).map(|i| (a[i] - b[i]).pow(2)).sum()
+}
+
+fn nearest(colour: u16, palette: &[u16]) -> usize {
+    (0..palette.len())
+        .min_by_key(|&i| distance(colour, palette[i]))
+        .unwrap()
+}
+
+// The distinct colours of a tile, most used first.
+fn colours(pixels: &Pixels) -> Vec<u16> {
+    let mut counts: Vec<(u16, usize)> = vec![];
+
+    for colour in pixels.iter().flatten() {
+        match counts.iter_mut().find(|(existing, _)| existing == colour) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((*colour, 1)),
+        }
+    }
+
+    counts.sort_by_key(|&(colour, count)| (usize::MAX - count, colour));
+    counts.into_iter().map(|(colour, _)| colour).collect()
+}
+
+// Median cut down to `limit` colours, each box ending up as the average of the
+// pixels in it.
+fn reduce(pixels: &Pixels, limit: usize) -> Vec<u16> {
+    let mut boxes: Vec<Vec<u16>> = vec![pixels.iter().flatten().copied().collect()];
+
+    while boxes.len() < limit {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colours)| colours.len() > 1)
+            .map(|(i, colours)| {
+                let (axis, range) = (0..3)
+                    .map(|axis| {
+                        let values = colours.iter().map(|&colour| channels(colour)[axis]);
+                        (axis, values.clone().max().unwrap() - values.min().unwrap())
+                    })
+                    .max_by_key(|&(_, range)| range)
+                    .unwrap();
+                (i, axis, range)
+            })
+            .filter(|&(_, _, range)| range > 0)
+            .max_by_key(|&(_, _, range)| range);
+
+        let Some((i, axis, _)) = widest else {
+            break;
+        };
+
+        let mut colours = boxes.swap_remove(i);
+        colours.sort_by_key(|&colour| channels(colour)[axis]);
+        let upper = colours.split_off(colours.len() / 2);
+        boxes.push(colours);
+        boxes.push(upper);
+    }
+
+    let mut reduced: Vec<u16> = boxes
+        .iter()
+        .map(|colours| {
+            let sum = colours.iter().fold([0; 3], |sum, &colour| {
+                let c = channels(colour);
+                [sum[0] + c[0], sum[1] + c[1], sum[2] + c[2]]
+            });
+            let n = colours.len() as i32;
+            (sum[0] / n) as u16 | (((sum[1] / n) as u16) << 5) | (((sum[2] / n) as u16) << 10)
+        })
+        .collect();
+    reduced.sort_unstable();
+    reduced.dedup();
+    reduced
+}
+
+// Each tile gets cut to at most 15 colours, then tiles are packed into banks
+// sharing as many colours as possible. Past 16 banks a tile goes to the bank
+// that fits it best, taking its free slots and the nearest colour for the rest.
+pub fn quantise(tiles: &[Pixels]) -> Quantised {
+    let sets: Vec<Vec<u16>> = tiles
+        .iter()
+        .map(|pixels| {
+            let colours = colours(pixels);
+            if colours.len() > BANK_COLOURS {
+                reduce(pixels, BANK_COLOURS)
+            } else {
+                colours
+            }
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..tiles.len()).collect();
+    order.sort_by_key(|&i| usize::MAX - sets[i].len());
+
+    let mut banks: Vec<Vec<u16>> = vec![];
+    for &i in &order {
+        let set = &sets[i];
+        let missing = |bank: &Vec<u16>| set.iter().filter(|colour| !bank.contains(colour)).count();
+
+        let fitting = banks
+            .iter()
+            .enumerate()
+            .filter(|(_, bank)| bank.len() + missing(bank) <= BANK_COLOURS)
+            .min_by_key(|(_, bank)| missing(bank))
+            .map(|(index, _)| index);
+
+        let index = match fitting {
+            Some(index) => index,
+            None if banks.len() < BANKS => {
+                banks.push(vec![]);
+                banks.len() - 1
+            }
+            None => (0..banks.len())
+                .min_by_key(|&index| {
+                    let bank = &banks[index];
+                    let free = BANK_COLOURS - bank.len();
+                    set.iter()
+                        .filter(|colour| !bank.contains(colour))
+                        .skip(free)
+                        .map(|&colour| distance(colour, bank[nearest(colour, bank)]))
+                        .sum::<i32>()
+                })
+                .unwrap(),
+        };
+
+        let bank = &mut banks[index];
+        for &colour in set {
+            if bank.len() < BANK_COLOURS && !bank.contains(&colour) {
+                bank.push(colour);
+            }
+        }
+    }
+
+    let palettes: Vec<[u16; 16]> = banks
+        .iter()
+        .map(|bank| {
+            let mut palette = [0; 16];
+            palette[1..=bank.len()].copy_from_slice(bank);
+            palette
+        })
+        .collect();
+
+    let tiles = tiles
+        .iter()
+        .zip(&sets)
+        .map(|(pixels, set)| {
+            // Tiles with the same colours always land in the same bank, so
+            // animation frames can be swapped in under a map cell's palette.
+            let bank = banks
+                .iter()
+                .position(|bank| set.iter().all(|colour| bank.contains(colour)))
+                .unwrap_or_else(|| {
+                    (0..banks.len())
+                        .min_by_key(|&index| {
+                            set.iter()
+                                .map(|&colour| {
+                                    distance(colour, banks[index][nearest(colour, &banks[index])])
+                                })
+                                .sum::<i32>()
+                        })
+                        .unwrap()
+                });
+
+            let mut indices = [0; 64];
+            for (index, pixel) in indices.iter_mut().zip(pixels) {
+                if let Some(colour) = pixel {
+                    *index = nearest(*colour, &banks[bank]) as u8 + 1;
+                }
+            }
+
+            Tile {
+                pixels: indices,
+                bank: bank as u8,
+            }
+        })
+        .collect();
+
+    Quantised { palettes, tiles }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A tile cycling through `colours`, with `None` for see-through pixels.
+    fn tile(colours: &[Option<u16>]) -> Pixels {
+        core::array::from_fn(|i| colours[i % colours.len()])
+    }
+
+    // The colours `tile` ended up as, after going through its bank.
+    fn shown(quantised: &Quantised, tile: usize) -> Vec<Option<u16>> {
+        let Tile { pixels, bank } = &quantised.tiles[tile];
+        let palette = quantised.palettes[*bank as usize];
+
+        pixels
+            .iter()
+            .map(|&index| (index != 0).then(|| palette[index as usize]))
+            .collect()
+    }
+
+    #[test]
+    fn colours_are_15_bit() {
+        assert_eq!(rgb15(0, 0, 0), 0);
+        assert_eq!(rgb15(255, 255, 255), 0x7fff);
+        assert_eq!(rgb15(0xff, 0x07, 0x80), 0x401f);
+    }
+
+    #[test]
+    fn few_colours_are_kept_exactly() {
+        let pixels = tile(&[Some(0x6586), None, Some(0x7aaf), Some(0x001f)]);
+        let quantised = quantise(&[pixels]);
+
+        assert_eq!(quantised.palettes.len(), 1);
+        assert_eq!(quantised.palettes[0][0], 0);
+        assert_eq!(shown(&quantised, 0), pixels.to_vec());
+    }
+
+    #[test]
+    fn see_through_tiles_are_all_zero() {
+        let quantised = quantise(&[[None; 64]]);
+
+        assert_eq!(quantised.tiles[0].pixels, [0; 64]);
+    }
+
+    #[test]
+    fn tiles_sharing_colours_share_a_bank() {
+        let (a, b, c) = (Some(1), Some(2), Some(3));
+        let tiles = [tile(&[a, b]), tile(&[b, c]), tile(&[c])];
+        let quantised = quantise(&tiles);
+
+        assert_eq!(quantised.palettes.len(), 1);
+        for (i, pixels) in tiles.iter().enumerate() {
+            assert_eq!(quantised.tiles[i].bank, 0);
+            assert_eq!(shown(&quantised, i), pixels.to_vec());
+        }
+    }
+
+    #[test]
+    fn tiles_that_dont_fit_together_get_their_own_banks() {
+        let first: Vec<_> = (0..10).map(Some).collect();
+        let second: Vec<_> = (100..110).map(Some).collect();
+        let tiles = [tile(&first), tile(&second), tile(&first[..3])];
+        let quantised = quantise(&tiles);
+
+        assert_eq!(quantised.palettes.len(), 2);
+        assert_ne!(quantised.tiles[0].bank, quantised.tiles[1].bank);
+        assert_eq!(quantised.tiles[2].bank, quantised.tiles[0].bank);
+        for (i, pixels) in tiles.iter().enumerate() {
+            assert_eq!(shown(&quantised, i), pixels.to_vec());
+        }
+    }
+
+    #[test]
+    fn tiles_with_more_than_15_colours_are_reduced() {
+        // 64 shades of red and green, all different.
+        let pixels: Pixels = core::array::from_fn(|i| Some(i as u16));
+        let quantised = quantise(&[pixels]);
+
+        let palette = quantised.palettes[0];
+        assert!(palette[1..].iter().filter(|&&colour| colour != 0).count() <= BANK_COLOURS);
+        for (original, shown) in pixels.iter().zip(shown(&quantised, 0)) {
+            let (original, shown) = (original.unwrap(), shown.unwrap());
+            assert!(
+                distance(original, shown) <= 8,
+                "{original:#x} became {shown:#x}"
+            );
+        }
+    }
+
+    #[test]
+    fn past_16_banks_tiles_share_the_nearest() {
+        // 17 tiles of 15 colours each, none in common, so one has to squeeze in.
+        let tiles: Vec<Pixels> = (0..17)
+            .map(|n| {
+                let colours: Vec<_> = (0..15).map(|i| Some(n << 10 | i)).collect();
+                tile(&colours)
+            })
+            .collect();
+        let quantised = quantise(&tiles);
+
+        assert_eq!(quantised.palettes.len(), BANKS);
+        assert!(
+            quantised
+                .tiles
+                .iter()
+                .all(|tile| (tile.bank as usize) < BANKS && !tile.pixels.contains(&0))
+        );
+    }
+}
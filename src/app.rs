@@ -4,28 +4,90 @@ use agb::{
     display::{
         object::{OamManaged, Object, Tag},
         tiled::{MapLoan, RegularBackgroundSize, RegularMap, Tiled0, TiledMap, VRamManager},
-        Priority,
     },
-    include_background_gfx,
-    input::{ButtonController, Tri},
+    fixnum::{Rect, Vector2D},
+    input::ButtonController,
     interrupt::VBlank,
     mgba::{DebugLevel, Mgba},
+    save::Error,
     Gba,
 };
 
-use mapgen::generate_background_map;
+use alloc::{format, string::String, vec::Vec};
+
+use mapgen::{generate_tile_animations, include_quantised_gfx};
+
+use logic::{
+    animation::{Animator, TileAnimation},
+    collision::{body, center, BODY_OFFSET},
+    depth::Layer,
+    input::{button, Action, Config, Input, Recording},
+    map::{tile_of, TILE_SIZE},
+    menu::MenuEvent,
+    motion::Motion,
+    rng::Rng,
+    script::Cutscene,
+    state::State,
+    terrain::{attributes_at, Rules},
+    trigger::Triggers,
+};
+
+use crate::{
+    bag::BagMenu,
+    cutscene::{self, Scene},
+    daynight::{Clock, DayNight},
+    depth,
+    direction::Direction,
+    effects::{Effect, Effects, Tone},
+    input::pressed,
+    interaction::{ahead, Event, Interactions, Trigger},
+    item::{Bag, Pickup},
+    map::{self, Map, Warp},
+    npc::Npc,
+    oam::Budget,
+    options::OptionsMenu,
+    pause::{Entry, PauseMenu},
+    prop::Placed,
+    save::{Save, SaveState},
+    sound,
+    stamp::Stamp,
+    state::{CAN_SWIM, SLIMES_MET},
+    text::TextBox,
+    tiles::BALL_TAG,
+    title::{self, Start},
+};
 
-use crate::tiles::BALL_TAG;
+include_quantised_gfx!(
+    test_bg,
+    tiles => "gfx/bg-tiles.png",
+    house_1 => deduplicate "$OUT_DIR/House-1.png",
+    water => "gfx/water.png",
+    pause => deduplicate "$OUT_DIR/Pause.png",
+);
 
-include_background_gfx!(test_bg, tiles => 256 "gfx/bg-tiles.png");
+const TILE_ANIMATIONS: &[TileAnimation] =
+    &generate_tile_animations!("gfx/bg-tiles.png", "gfx/water.png", 12);
 
 const INITIAL_COORDINATES: (i32, i32) = (104, 64);
 const STEP: i32 = 24;
+pub const FADE_FRAMES: u16 = 16;
+// How long area names and encounters stay up in the text box.
+const POPUP_FRAMES: u16 = 90;
+// The two blues in gfx/water.png, swapped back and forth.
+const WATER_COLOURS: [u16; 2] = [0x6586, 0x7aaf];
+const WATER_PERIOD: u16 = 24;
+// Where a new game starts, which is also where the camera starts unscrolled.
+const NEW_GAME: Warp = Warp {
+    map: "bg",
+    spawn: (14, 9),
+};
+// A new game starts in the morning, the clock is saved from then on.
+const NEW_GAME_MINUTES: u16 = 8 * 60;
 
 trait Entity<'a> {
     fn new(tag: &'static Tag, gfx: &'a OamManaged) -> Self;
     fn place(&mut self, coords: (i32, i32));
-    fn process(&mut self, input: &ButtonController);
+    fn process(&mut self, input: &Input);
     fn tick(&mut self, gfx: &'a OamManaged);
 }
 
@@ -50,7 +112,7 @@ impl<'a> Entity<'a> for Char<'a> {
         self.sprite.set_position((x, y)).show();
     }
 
-    fn process(&mut self, input: &ButtonController) {
+    fn process(&mut self, input: &Input) {
         todo!()
     }
 
@@ -61,188 +123,715 @@ impl<'a> Entity<'a> for Char<'a> {
 
 struct Background<'a> {
     bg: MapLoan<'a, RegularMap>,
+    // The tops of stamped props, drawn over the sprites. The rest of each is
+    // drawn into `bg`.
+    overhang: MapLoan<'a, RegularMap>,
     tilemap: [[usize; 32]; 32],
+    animator: Animator,
 }
 
 impl<'a> Background<'a> {
-    fn new(bg_gfx: &'a Tiled0, mut vram: &mut VRamManager) -> Self {
+    fn new(bg_gfx: &'a Tiled0, vram: &mut VRamManager, map: &Map) -> Self {
         vram.set_background_palettes(test_bg::PALETTES);
 
-        let tileset = &test_bg::tiles.tiles;
-        let tilemap = generate_background_map!("gfx/bg.png");
-
-        let mut bg = bg_gfx.background(
-            Priority::P0,
+        let format = test_bg::tiles.tiles.format();
+        let bg = bg_gfx.background(
+            depth::GROUND,
             RegularBackgroundSize::Background32x32,
-            tileset.format(),
+            format,
         );
+        let overhang = bg_gfx.background(
+            depth::OVERHANG,
+            RegularBackgroundSize::Background32x32,
+            format,
+        );
+
+        let mut background = Self {
+            bg,
+            overhang,
+            tilemap: map.tilemap(),
+            animator: Animator::new(TILE_ANIMATIONS),
+        };
+        background.load(vram, map);
+        background.bg.set_visible(true);
+        background.overhang.set_visible(true);
+
+        background
+    }
+
+    fn load(&mut self, mut vram: &mut VRamManager, map: &Map) {
+        let tileset = &test_bg::tiles.tiles;
+        self.tilemap = map.tilemap();
 
         for y in 0..32u16 {
             for x in 0..32u16 {
-                bg.set_tile(
+                self.bg.set_tile(
                     &mut vram,
                     (x, y),
                     &tileset,
-                    test_bg::tiles.tile_settings[tilemap[y as usize][x as usize]],
+                    test_bg::tiles.tile_settings[self.tilemap[y as usize][x as usize]],
                 );
             }
         }
 
-        bg.commit(&mut vram);
-        bg.set_visible(true);
+        // Whatever is above a prop's footprint can have someone standing behind
+        // it, so it goes over the sprites.
+        self.overhang.clear(vram);
+        for prop in map.props {
+            if let Some(stamp) = stamp_for(prop.tag) {
+                let (x, y) = prop.position;
+                let tile = ((x / TILE_SIZE) as u16, (y / TILE_SIZE) as u16);
+                let split = (prop.footprint.1 / TILE_SIZE) as u16;
+
+                stamp.draw_rows(&mut self.overhang, vram, tile, 0..split);
+                stamp.draw_rows(&mut self.bg, vram, tile, split..u16::MAX);
+            }
+        }
 
-        Self { bg, tilemap }
+        self.bg.commit(&mut vram);
+        self.overhang.commit(vram);
+    }
+
+    // Swaps animated tiles' graphics in VRAM, so every cell using them changes
+    // at once. Tiles the current map doesn't use aren't in VRAM and are skipped.
+    fn animate(&mut self, vram: &mut VRamManager) {
+        for (tile, frame) in self.animator.tick() {
+            vram.replace_tile(
+                &test_bg::tiles.tiles,
+                tile as u16,
+                &test_bg::water.tiles,
+                frame as u16,
+            );
+        }
+    }
+
+    fn scroll(&mut self, vram: &mut VRamManager, scroll: (i16, i16)) {
+        self.bg.set_scroll_pos(scroll);
+        self.overhang.set_scroll_pos(scroll);
+
+        self.bg.commit(vram);
+        self.overhang.commit(vram);
     }
 }
 
+// Quitting from the pause menu goes back to the title.
 pub fn run(mut gba: Gba) -> ! {
+    loop {
+        let saved = Save::new(&mut gba.save)
+            .ok()
+            .and_then(|mut save| save.load());
+        let (start, frames) = title::show(
+            &mut gba,
+            saved.map_or(Config::default(), |saved| saved.config),
+            saved.is_some(),
+        );
+
+        play(&mut gba, start, frames);
+    }
+}
+
+// Returns once the player quits and the screen has faded out. Trigger zone
+// rolls are seeded from how long the title was up.
+fn play(gba: &mut Gba, start: Start, seed: u32) {
     let vblank = VBlank::get();
-    let mut input = ButtonController::new();
+    let mut controller = ButtonController::new();
 
     let gfx = gba.display.object.get_managed();
     let (bg_gfx, mut vram) = gba.display.video.tiled0();
 
-    let mut background = Background::new(&bg_gfx, &mut vram);
+    let mut save = Save::new(&mut gba.save);
+    // Loaded even for a new game, so saving carries on after the newest slot.
+    // Settings carry over to a new game too, just not the progress.
+    let loaded = save.as_mut().ok().and_then(Save::load);
+    let mut input = Input::new(loaded.map_or(Config::default(), |saved| saved.config));
+    let saved = loaded.filter(|_| start == Start::Continue);
+
+    let mut map = map::find(saved.map_or(NEW_GAME.map, |saved| saved.map));
+    let mut background = Background::new(&bg_gfx, &mut vram, map);
+
+    let mut effects = Effects::new(
+        gba.display.blend.get(),
+        &[background.bg.background(), background.overhang.background()],
+    );
+    // Coming in from the title, which faded out.
+    effects.start(Effect::FadeIn(Tone::Black), FADE_FRAMES);
+    effects.start(
+        Effect::PaletteCycle {
+            palettes: test_bg::PALETTES,
+            colours: &WATER_COLOURS,
+            period: WATER_PERIOD,
+        },
+        0,
+    );
+
+    let minutes = saved.map_or(NEW_GAME_MINUTES, |saved| saved.minutes);
+    let mut day_night = DayNight::new(test_bg::PALETTES, Clock::new(minutes));
+    let mut phase = day_night.phase();
+
+    let mut logger = Mgba::new();
+    let mut interactions = Interactions::new(map.objects);
 
     let mut char = Char::new(BALL_TAG, &gfx);
     char.place(INITIAL_COORDINATES);
 
-    let mut scroll_x = 0i16;
-    let mut scroll_y = 0i16;
+    let mut npcs = spawn_npcs(&gfx, map);
+    let mut props = spawn_props(&gfx, map);
+    let mut pickups = spawn_pickups(&gfx, &interactions);
+    let mut budget = Budget::new(&gfx);
+
+    let (mut scroll_x, mut scroll_y) = saved.map_or(spawn_scroll(NEW_GAME), |saved| saved.scroll);
+    let mut motion = Motion::new(position((scroll_x, scroll_y)));
+    let mut facing = saved.map_or(Direction::Down, |saved| saved.facing);
+    let mut on_warp = interactions
+        .warp_touching(player_body((scroll_x, scroll_y)))
+        .is_some();
+    let mut triggers = Triggers::new(map.triggers, player_body((scroll_x, scroll_y)));
+    let mut fired = Vec::new();
+    let mut rng = Rng::new(seed);
+    let mut transition: Option<Warp> = None;
+    let mut recording: Option<Recording> = None;
+    let mut playing: Option<Cutscene> = None;
+    let mut state = saved.map_or(State::default(), |saved| saved.state);
+    let mut bag = saved.map_or(Bag::default(), |saved| saved.bag);
+    let mut bag_menu: Option<BagMenu> = None;
+    let mut options: Option<OptionsMenu> = None;
+    let mut pause = PauseMenu::new(
+        &bg_gfx,
+        &mut vram,
+        &gfx,
+        test_bg::tiles.tiles.format(),
+        Stamp::new("Pause", &test_bg::pause),
+    );
+    let mut text = TextBox::new(&bg_gfx, &test_bg::PALETTES[0]);
+    // Frames left before the popup in the text box goes, 0 when there isn't one.
+    let mut popup: u16 = 0;
+    // Dialogue in the text box stays up until it's confirmed, and the player
+    // stands still until then.
+    let mut reading = false;
+    let mut quitting = false;
 
     loop {
         vblank.wait_for_vblank();
-        input.update();
-
-        match input.x_tri() {
-            Tri::Positive => {
-                if !path_right_blocked((scroll_x + 1, scroll_y), background.tilemap) {
-                    scroll_x += 1
+        controller.update();
+        input.update(pressed(&controller));
+
+        // Hold L and press R to record input for a replay test in the logic crate.
+        if input.buttons().is_pressed(button::L) && input.buttons().is_just_pressed(button::R) {
+            let position = position((scroll_x, scroll_y));
+
+            match recording.take() {
+                Some(recording) => log(
+                    &mut logger,
+                    format_args!("Replay: {recording} ending at {position:?}"),
+                ),
+                None => {
+                    log(
+                        &mut logger,
+                        format_args!("Recording on {} from {:?}", map.name, position),
+                    );
+                    recording = Some(Recording::new());
                 }
             }
-            Tri::Negative => {
-                if !path_left_blocked((scroll_x - 1, scroll_y), background.tilemap) {
-                    scroll_x -= 1
+        }
+
+        if let Some(recording) = recording.as_mut() {
+            recording.push(input.buttons().pressed());
+        }
+
+        // The clock and animated tiles stop while the game is paused.
+        if !pause.is_open() {
+            day_night.tick(&mut vram);
+            budget.tint(day_night.multipliers());
+            background.animate(&mut vram);
+        }
+
+        // After the clock, so cycled colours go over what it wrote.
+        effects.tick(&mut vram, |colour| day_night.tint(colour));
+
+        if quitting && !effects.fading() {
+            background.bg.set_visible(false);
+            background.overhang.set_visible(false);
+            text.hide(&mut vram);
+
+            return;
+        }
+
+        if day_night.phase() != phase {
+            phase = day_night.phase();
+
+            let minutes = day_night.clock.minutes();
+            log(
+                &mut logger,
+                format_args!("{:02}:{:02} {:?}", minutes / 60, minutes % 60, phase),
+            );
+        }
+
+        if let Some(warp) = transition.take_if(|_| !effects.fading()) {
+            map = map::find(warp.map);
+            background.load(&mut vram, map);
+            interactions = Interactions::new(map.objects);
+            npcs = spawn_npcs(&gfx, map);
+            props = spawn_props(&gfx, map);
+            pickups = spawn_pickups(&gfx, &interactions);
+
+            (scroll_x, scroll_y) = spawn_scroll(warp);
+            motion = Motion::new(position((scroll_x, scroll_y)));
+            on_warp = interactions
+                .warp_touching(player_body((scroll_x, scroll_y)))
+                .is_some();
+            triggers = Triggers::new(map.triggers, player_body((scroll_x, scroll_y)));
+
+            effects.start(Effect::FadeIn(Tone::Black), FADE_FRAMES);
+        }
+
+        // A cutscene takes over from the player, who can only close dialogue.
+        if let Some(cutscene) = playing.as_mut() {
+            if text.is_showing() && input.take(Action::Confirm) {
+                text.hide(&mut vram);
+            }
+
+            cutscene.tick(&mut Scene {
+                motion: &mut motion,
+                facing: &mut facing,
+                npcs: &mut npcs,
+                tilemap: &background.tilemap,
+                props: &props,
+                pickups: &pickups,
+                effects: &mut effects,
+                text: &mut text,
+                vram: &mut vram,
+                state: &mut state,
+                transition: &mut transition,
+                logger: &mut logger,
+            });
+
+            if cutscene.finished() {
+                playing = None;
+            }
+
+            (scroll_x, scroll_y) = scroll_for(&motion);
+        } else if reading {
+            if input.take(Action::Confirm) {
+                text.hide(&mut vram);
+                reading = false;
+            }
+        } else if let Some(menu) = bag_menu.as_mut() {
+            let event = menu.tick(&mut input);
+
+            match event {
+                Some(MenuEvent::Moved(_)) => {
+                    let (item, count) = menu.pointed();
+                    log(&mut logger, format_args!("{} x{}", item.name(), count))
                 }
+                Some(MenuEvent::Closed) => bag_menu = None,
+                _ => {}
             }
-            _ => {}
-        };
 
-        match input.y_tri() {
-            Tri::Positive => {
-                if !path_down_blocked((scroll_x, scroll_y + 1), background.tilemap) {
-                    scroll_y += 1
+            if let Some(event) = event {
+                sound::play(&mut logger, event.sound());
+            }
+        } else if let Some(menu) = options.as_mut() {
+            let event = menu.tick(&mut input);
+
+            match event {
+                Some(MenuEvent::Closed) => {
+                    // Saved on the way out so new bindings survive a reset.
+                    if menu.changed(&input.config) {
+                        store(
+                            &mut save,
+                            &snapshot(
+                                map,
+                                (scroll_x, scroll_y),
+                                facing,
+                                &input.config,
+                                &state,
+                                &bag,
+                                &day_night.clock,
+                            ),
+                            &mut logger,
+                        );
+                    }
+
+                    text.hide(&mut vram);
+                    pause.set_visible(true);
+                    options = None;
                 }
+                Some(_) => menu.draw(&mut text, &mut vram, &input.config),
+                None => {}
             }
-            Tri::Negative => {
-                if !path_up_blocked((scroll_x, scroll_y - 1), background.tilemap) {
-                    scroll_y -= 1
+
+            if let Some(event) = event {
+                sound::play(&mut logger, event.sound());
+            }
+        } else if pause.is_open() {
+            let event = pause.tick(&mut input);
+
+            match event {
+                Some(MenuEvent::Chosen(index)) => match Entry::ALL[index] {
+                    Entry::Items => {
+                        bag_menu = BagMenu::new(&gfx, &bag);
+                        input.clear();
+
+                        match bag_menu.as_ref().map(BagMenu::pointed) {
+                            Some((item, count)) => {
+                                log(&mut logger, format_args!("{} x{}", item.name(), count))
+                            }
+                            None => log(&mut logger, format_args!("The bag is empty.")),
+                        }
+                    }
+                    Entry::Save => {
+                        store(
+                            &mut save,
+                            &snapshot(
+                                map,
+                                (scroll_x, scroll_y),
+                                facing,
+                                &input.config,
+                                &state,
+                                &bag,
+                                &day_night.clock,
+                            ),
+                            &mut logger,
+                        );
+                    }
+                    Entry::Options => {
+                        // The list is tall enough to run into the window.
+                        pause.set_visible(false);
+                        options = Some(OptionsMenu::new(&mut text, &mut vram, &input.config));
+                        input.clear();
+                    }
+                    Entry::Quit => {
+                        pause.close();
+                        quitting = true;
+                        effects.start(Effect::FadeOut(Tone::Black), FADE_FRAMES);
+                    }
+                },
+                Some(MenuEvent::Closed) => pause.close(),
+                _ => {}
+            }
+
+            if let Some(event) = event {
+                sound::play(&mut logger, event.sound());
+            }
+        } else if !effects.fading() {
+            if let Some(direction) = Direction::from_input(&input) {
+                facing = direction;
+            }
+
+            (scroll_x, scroll_y) =
+                move_player(&input, &mut motion, &background.tilemap, &state, |area| {
+                    npcs.iter().any(|npc| npc.body().touches(area))
+                        || scenery(area, &props, &pickups, &state)
+                });
+
+            let player = player_body((scroll_x, scroll_y));
+            let mut pending_warp: Option<Warp> = None;
+
+            if popup > 0 {
+                popup -= 1;
+                if popup == 0 {
+                    text.hide(&mut vram);
                 }
             }
-            _ => {}
-        };
-        // char.process(&input);
-        // char.tick(&gfx);
 
-        background.bg.set_scroll_pos((scroll_x, scroll_y));
-        background.bg.commit(&mut vram);
+            let ground = attributes_at(&background.tilemap, center(player));
+            if triggers.update(player, ground, &state, &mut rng, &mut fired) {
+                sound::step(&mut logger, ground.footstep);
+            }
 
-        gfx.commit();
-    }
-}
+            for &trigger in fired.iter() {
+                match trigger {
+                    Trigger::Cutscene(name) => {
+                        // The cutscene would wait on a popup as if it were
+                        // dialogue.
+                        popup = 0;
+                        text.hide(&mut vram);
+                        playing = Some(Cutscene::new(cutscene::find(name)));
+                    }
+                    Trigger::AreaName(name) => {
+                        log(&mut logger, format_args!("~ {name} ~"));
+                        text.show(&mut vram, name);
+                        popup = POPUP_FRAMES;
+                    }
+                    Trigger::Encounter(name) => {
+                        state.add(SLIMES_MET, 1);
+                        log(
+                            &mut logger,
+                            format_args!(
+                                "A wild {} appeared! That's {} so far.",
+                                name,
+                                state.var(SLIMES_MET)
+                            ),
+                        );
+                        text.show(&mut vram, &format!("A wild {name} appeared!"));
+                        popup = POPUP_FRAMES;
+
+                        effects.start(Effect::FadeIn(Tone::White), FADE_FRAMES);
+                        effects.start(Effect::Flash, POPUP_FRAMES);
+                    }
+                }
+            }
 
-fn path_right_blocked((scroll_x, scroll_y): (i16, i16), tilemap: [[usize; 32]; 32]) -> bool {
-    let init_pos_x = INITIAL_COORDINATES.0 as i16;
-    let init_pos_y = INITIAL_COORDINATES.1 as i16;
+            if input.take(Action::Confirm) {
+                let ahead = ahead(player, facing);
+
+                let said = if let Some(npc) =
+                    npcs.iter_mut().find(|npc| npc.body().contains_point(ahead))
+                {
+                    npc.talk_to(player);
+                    Some(String::from(npc.text))
+                } else if let Some(index) = interactions.at(tile_of(ahead), &state) {
+                    match interactions.trigger(index, &mut state, &mut bag) {
+                        Event::Read(text) => Some(String::from(text)),
+                        Event::Found(item) => Some(format!("Found {}!", item.name())),
+                        Event::Full(item) => Some(format!(
+                            "There's no room in the bag for the {}.",
+                            item.name()
+                        )),
+                        Event::Empty => Some(String::from("It's empty.")),
+                        Event::Locked => Some(String::from("It's locked.")),
+                        Event::Enter(warp) => {
+                            pending_warp = Some(warp);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                if let Some(said) = said {
+                    log(&mut logger, format_args!("{said}"));
+                    text.show(&mut vram, &said);
+                    popup = 0;
+                    reading = true;
+                }
+            }
 
-    let cur_offset_x = init_pos_x + scroll_x;
+            if input.take(Action::Menu) {
+                pause.open();
+                input.clear();
+            }
 
-    if cur_offset_x % 8 != 0 {
-        return false;
-    }
+            if input.take(Action::Save) {
+                store(
+                    &mut save,
+                    &snapshot(
+                        map,
+                        (scroll_x, scroll_y),
+                        facing,
+                        &input.config,
+                        &state,
+                        &bag,
+                        &day_night.clock,
+                    ),
+                    &mut logger,
+                );
+            }
 
-    let cur_tile = cur_offset_x / 8;
-    let last_tile = cur_tile;
-    let next_tile = cur_tile + 3;
+            // Only warp when stepping onto the tile, so arriving on top of one doesn't
+            // bounce the player straight back.
+            let warp = interactions.warp_touching(player);
+            if !on_warp {
+                pending_warp = pending_warp.or(warp);
+            }
+            on_warp = warp.is_some();
 
-    let cur_offset_y = init_pos_y + scroll_y;
-    let cur_tile_y = cur_offset_y / 8;
+            if pending_warp.is_some() {
+                transition = pending_warp;
+                effects.start(Effect::FadeOut(Tone::Black), FADE_FRAMES);
+            }
+        }
 
-    let (tile1_y, tile2_y, tile3_y) = (cur_tile_y + 1, cur_tile_y + 2, cur_tile_y + 3);
+        // The camera follows the ground under the player, so a hop only lifts
+        // the sprite.
+        char.sprite
+            .set_sprite(budget.load(char.tag.animation_sprite(char.frame)));
+        char.sprite.set_position((
+            INITIAL_COORDINATES.0,
+            INITIAL_COORDINATES.1 - motion.height(),
+        ));
 
-    tilemap[tile1_y as usize][next_tile as usize] != 5
-        || tilemap[tile2_y as usize][next_tile as usize] != 5
-        || tilemap[tile3_y as usize][next_tile as usize] != 5
-}
+        let player = player_body((scroll_x, scroll_y));
+        depth::sort(&mut char.sprite, player, Layer::Player);
 
-fn path_left_blocked((pos_x, pos_y): (i16, i16), tilemap: [[usize; 32]; 32]) -> bool {
-    let init_pos_x = INITIAL_COORDINATES.0 as i16;
-    let init_pos_y = INITIAL_COORDINATES.1 as i16;
+        let scroll = Vector2D::new(scroll_x as i32, scroll_y as i32);
 
-    let cur_offset_x = init_pos_x + pos_x;
+        // Menus are drawn over the world, so they come out of this frame's
+        // budget only while they're open.
+        if let Some(menu) = bag_menu.as_ref() {
+            budget.reserve(menu.objects());
+        }
+        if pause.is_open() {
+            budget.reserve(pause.objects());
+        }
 
-    if cur_offset_x % 8 != 0 {
-        return false;
-    }
+        for prop in props.iter_mut() {
+            prop.render(scroll, &mut budget);
+        }
 
-    let cur_tile = cur_offset_x / 8;
-    let last_tile = cur_tile;
-    let next_tile = cur_tile + 4;
+        for pickup in pickups.iter_mut() {
+            pickup.render(scroll, &mut budget, state.flag(pickup.taken));
+        }
 
-    let cur_offset_y = init_pos_y + pos_y;
-    let cur_tile_y = cur_offset_y / 8;
+        for i in 0..npcs.len() {
+            let (before, rest) = npcs.split_at_mut(i);
+            let (npc, after) = rest.split_first_mut().unwrap();
+
+            // Npcs hold still for cutscenes, unless the script moves them,
+            // while the game is paused and while someone's reading.
+            if playing.is_none() && !pause.is_open() && !reading {
+                npc.tick(&background.tilemap, |area| {
+                    area.touches(player)
+                        || scenery(area, &props, &pickups, &state)
+                        || before
+                            .iter()
+                            .chain(after.iter())
+                            .any(|other| other.body().touches(area))
+                });
+            }
+            npc.render(scroll, &mut budget);
+        }
 
-    let (tile1_y, tile2_y, tile3_y) = (cur_tile_y + 1, cur_tile_y + 2, cur_tile_y + 3);
+        if let Some(overrun) = budget.finish() {
+            log(
+                &mut logger,
+                format_args!(
+                    "Over sprite budget: {} objects, {} tiles",
+                    overrun.objects, overrun.tiles
+                ),
+            );
+        }
+
+        background.scroll(&mut vram, (scroll_x, scroll_y));
 
-    tilemap[tile1_y as usize][last_tile as usize] != 5
-        || tilemap[tile2_y as usize][last_tile as usize] != 5
-        || tilemap[tile3_y as usize][last_tile as usize] != 5
+        gfx.commit();
+    }
 }
 
-fn path_up_blocked((pos_x, pos_y): (i16, i16), tilemap: [[usize; 32]; 32]) -> bool {
-    let init_pos_x = INITIAL_COORDINATES.0 as i16;
-    let init_pos_y = INITIAL_COORDINATES.1 as i16;
+// Everything that goes in a save, as it is right now.
+fn snapshot(
+    map: &Map,
+    scroll: (i16, i16),
+    facing: Direction,
+    config: &Config,
+    state: &State,
+    bag: &Bag,
+    clock: &Clock,
+) -> SaveState {
+    SaveState {
+        map: map.name,
+        scroll,
+        facing,
+        config: *config,
+        state: *state,
+        bag: *bag,
+        minutes: clock.minutes(),
+    }
+}
 
-    let cur_offset_y = init_pos_y + pos_y;
+fn store(save: &mut Result<Save, Error>, snapshot: &SaveState, logger: &mut Option<Mgba>) {
+    let stored = save
+        .as_mut()
+        .map_err(|error| error.clone())
+        .and_then(|save| save.store(snapshot));
 
-    if cur_offset_y % 8 != 0 {
-        return false;
+    match stored {
+        Ok(()) => log(logger, format_args!("Saved.")),
+        Err(error) => log(logger, format_args!("Couldn't save: {error:?}")),
     }
+}
 
-    let cur_tile = cur_offset_y / 8;
-    let last_tile = cur_tile;
-    let next_tile = cur_tile + 4;
+fn move_player(
+    input: &Input,
+    motion: &mut Motion,
+    tilemap: &[[usize; 32]; 32],
+    state: &State,
+    occupied: impl Fn(Rect<i32>) -> bool,
+) -> (i16, i16) {
+    let rules = Rules {
+        swimming: state.flag(CAN_SWIM),
+    };
+
+    motion.steer(input.movement(), input.is_held(Action::Run));
+    motion.advance(tilemap, rules, occupied);
+
+    scroll_for(motion)
+}
 
-    let cur_offset_x = init_pos_x + pos_x;
-    let cur_tile_x = cur_offset_x / 8;
+// Whether props or items still lying around are in the way.
+pub fn scenery(area: Rect<i32>, props: &[Placed], pickups: &[Pickup], state: &State) -> bool {
+    props.iter().any(|prop| prop.footprint().touches(area))
+        || pickups
+            .iter()
+            .any(|pickup| !state.flag(pickup.taken) && pickup.footprint().touches(area))
+}
 
-    let (tile1_x, tile2_x, tile3_x) = (cur_tile_x + 1, cur_tile_x + 2, cur_tile_x + 3);
+fn scroll_for(motion: &Motion) -> (i16, i16) {
+    let (x, y) = motion.pixel();
+    (
+        x - INITIAL_COORDINATES.0 as i16,
+        y - INITIAL_COORDINATES.1 as i16,
+    )
+}
 
-    tilemap[last_tile as usize][tile1_x as usize] != 5
-        || tilemap[last_tile as usize][tile2_x as usize] != 5
-        || tilemap[last_tile as usize][tile3_x as usize] != 5
+fn spawn_npcs<'a>(gfx: &'a OamManaged, map: &Map) -> Vec<Npc<'a>> {
+    map.npcs
+        .iter()
+        .map(|&(position, behaviour, text)| Npc::new(gfx, position, behaviour, text))
+        .collect()
 }
 
-fn path_down_blocked((pos_x, pos_y): (i16, i16), tilemap: [[usize; 32]; 32]) -> bool {
-    let init_pos_x = INITIAL_COORDINATES.0 as i16;
-    let init_pos_y = INITIAL_COORDINATES.1 as i16;
+// Props with a stamp are drawn by Background::load, the rest become sprites.
+fn spawn_props<'a>(gfx: &'a OamManaged, map: &Map) -> Vec<Placed<'a>> {
+    map.props
+        .iter()
+        .map(|prop| match stamp_for(prop.tag) {
+            Some(_) => Placed::stamped(prop),
+            None => Placed::new(gfx, prop),
+        })
+        .collect()
+}
 
-    let cur_offset_y = init_pos_y + pos_y;
+fn spawn_pickups<'a>(gfx: &'a OamManaged, interactions: &Interactions) -> Vec<Pickup<'a>> {
+    interactions
+        .pickups()
+        .map(|(item, taken, tile)| Pickup::new(gfx, item, taken, tile))
+        .collect()
+}
 
-    if cur_offset_y % 8 != 0 {
-        return false;
+fn stamp_for(tag: &str) -> Option<Stamp> {
+    match tag {
+        "House-1" => Some(Stamp::new(tag, &test_bg::house_1)),
+        _ => None,
     }
+}
+
+// Warp spawns are the tile the top left of the player's body lands on.
+fn spawn_scroll(warp: Warp) -> (i16, i16) {
+    let (x, y) = warp.spawn;
 
-    let cur_tile = cur_offset_y / 8;
-    let last_tile = cur_tile;
-    let next_tile = cur_tile + 3;
+    (
+        (x * TILE_SIZE - BODY_OFFSET.0 - INITIAL_COORDINATES.0) as i16,
+        (y * TILE_SIZE - BODY_OFFSET.1 - INITIAL_COORDINATES.1) as i16,
+    )
+}
 
-    let cur_offset_x = init_pos_x + pos_x;
-    let cur_tile_x = cur_offset_x / 8;
+pub fn log(logger: &mut Option<Mgba>, output: Arguments) {
+    if let Some(logger) = logger {
+        let _ = logger.print(output, DebugLevel::Info);
+    }
+}
 
-    let (tile1_x, tile2_x, tile3_x) = (cur_tile_x + 1, cur_tile_x + 2, cur_tile_x + 3);
+fn position((scroll_x, scroll_y): (i16, i16)) -> (i16, i16) {
+    (
+        INITIAL_COORDINATES.0 as i16 + scroll_x,
+        INITIAL_COORDINATES.1 as i16 + scroll_y,
+    )
+}
 
-    tilemap[next_tile as usize][tile1_x as usize] != 5
-        || tilemap[next_tile as usize][tile2_x as usize] != 5
-        || tilemap[next_tile as usize][tile3_x as usize] != 5
+fn player_body((scroll_x, scroll_y): (i16, i16)) -> Rect<i32> {
+    body(Vector2D::new(
+        INITIAL_COORDINATES.0 + scroll_x as i32,
+        INITIAL_COORDINATES.1 + scroll_y as i32,
+    ))
 }
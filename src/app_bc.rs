@@ -1,38 +1,32 @@
 use agb::{
     display::object::{OamManaged, Object, Tag},
-    input::{ButtonController, Tri},
+    input::ButtonController,
     interrupt::VBlank,
     Gba,
 };
 
-use crate::tiles::{CHAR_BACK, CHAR_FRONT, CHAR_LEFT, CHAR_RIGHT};
+use logic::{
+    collision::{body, center},
+    input::{Config, Diagonal, Input},
+    movement::{GridWalker, MovementType},
+    state::State,
+    terrain::{attributes_at, Rules},
+};
+
+use crate::{
+    input::pressed,
+    map,
+    state::CAN_SWIM,
+    tiles::{CHAR_BACK, CHAR_FRONT, CHAR_LEFT, CHAR_RIGHT},
+};
 
 const INITIAL_COORDINATES: (i32, i32) = (104, 64);
 const STEP: i32 = 32;
 
-#[derive(Clone, Copy)]
-enum MovementType {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-struct Movement {
-    tp: MovementType,
-    step: i32,
-}
-
-impl Movement {
-    fn new(tp: MovementType, step: i32) -> Self {
-        Self { tp, step }
-    }
-}
-
 trait Entity<'a> {
     fn new(tag: &'static Tag, gfx: &'a OamManaged) -> Self;
     fn place(&mut self, coords: (i32, i32));
-    fn process(&mut self, input: &ButtonController);
+    fn process(&mut self, input: &Input);
     fn tick(&mut self, gfx: &'a OamManaged);
 }
 
@@ -40,7 +34,10 @@ struct Char<'a> {
     tag: &'a Tag,
     frame: usize,
     sprite: Object<'a>,
-    movement: Option<Movement>,
+    walker: GridWalker,
+    moved: Option<MovementType>,
+    tilemap: [[usize; 32]; 32],
+    rules: Rules,
 }
 
 impl<'a> Entity<'a> for Char<'a> {
@@ -51,99 +48,71 @@ impl<'a> Entity<'a> for Char<'a> {
             tag,
             frame,
             sprite: gfx.object_sprite(tag.animation_sprite(frame)),
-            movement: None,
+            walker: GridWalker::new((0, 0), STEP),
+            moved: None,
+            // The start map isn't drawn here, but its ground is walked on,
+            // by the rules of a new game since there's no story to load.
+            tilemap: map::find("bg").tilemap(),
+            rules: Rules {
+                swimming: State::default().flag(CAN_SWIM),
+            },
         }
     }
 
     fn place(&mut self, (x, y): (i32, i32)) {
+        self.walker.position = (x, y);
         self.sprite.set_position((x, y)).show();
     }
 
-    fn process(&mut self, input: &ButtonController) {
-        if let Some(_) = self.movement {
+    fn process(&mut self, input: &Input) {
+        let tilemap = &self.tilemap;
+        self.moved = self.walker.frame(input.movement(), self.rules, |(x, y)| {
+            attributes_at(tilemap, center(body((x, y).into())))
+        });
+    }
+
+    fn tick(&mut self, gfx: &'a OamManaged) {
+        let Some(tp) = self.moved else {
             return;
-        }
+        };
 
-        match input.x_tri() {
-            Tri::Positive => self.movement = Some(Movement::new(MovementType::Right, STEP)),
-            Tri::Negative => self.movement = Some(Movement::new(MovementType::Left, STEP)),
-            _ => {}
-        }
+        self.tag = match tp {
+            MovementType::Up => CHAR_BACK,
+            MovementType::Down => CHAR_FRONT,
+            MovementType::Left => CHAR_LEFT,
+            MovementType::Right => CHAR_RIGHT,
+        };
 
-        match input.y_tri() {
-            Tri::Positive => self.movement = Some(Movement::new(MovementType::Down, STEP)),
-            Tri::Negative => self.movement = Some(Movement::new(MovementType::Up, STEP)),
-            _ => {}
-        }
-    }
+        let (x, y) = self.walker.position;
+        self.sprite.set_position((x, y - self.walker.height()));
+        self.sprite
+            .set_sprite(gfx.sprite(self.tag.animation_sprite(self.frame)));
 
-    fn tick(&mut self, gfx: &'a OamManaged) {
-        match &self.movement {
-            Some(movement) => {
-                match movement.tp {
-                    MovementType::Up => {
-                        self.sprite.set_y(self.sprite.y() - 1);
-                        self.tag = CHAR_BACK;
-                    }
-                    MovementType::Down => {
-                        self.sprite.set_y(self.sprite.y() + 1);
-                        self.tag = CHAR_FRONT
-                    }
-                    MovementType::Left => {
-                        self.sprite.set_x(self.sprite.x() - 1);
-                        self.tag = CHAR_LEFT;
-                    }
-                    MovementType::Right => {
-                        self.sprite.set_x(self.sprite.x() + 1);
-                        self.tag = CHAR_RIGHT;
-                    }
-                };
-
-                self.sprite
-                    .set_sprite(gfx.sprite(self.tag.animation_sprite(self.frame)));
-
-                self.frame = if self.frame > self.tag.sprites().len() {
-                    0
-                } else {
-                    self.frame + 1
-                };
-
-                if movement.step == 0 {
-                    self.movement = None;
-                    return;
-                }
-
-                self.movement = Some(Movement::new(movement.tp, movement.step - 1));
-            }
-            _ => {}
-        }
+        self.frame = (self.frame + 1) % self.tag.sprites().len();
     }
 }
 
 pub fn run(mut gba: Gba) -> ! {
     let vblank = VBlank::get();
-    let mut input = ButtonController::new();
+    let mut controller = ButtonController::new();
+    // Steps are on the grid, so holding two directions turns towards the newer one.
+    let mut input = Input::new(Config {
+        diagonal: Diagonal::LastPressed,
+        ..Config::default()
+    });
 
     let gfx = gba.display.object.get_managed();
 
     let mut char = Char::new(CHAR_FRONT, &gfx);
     char.place(INITIAL_COORDINATES);
 
-    let mut count = 0;
-
     loop {
         vblank.wait_for_vblank();
-        input.update();
-        char.tick(&gfx);
-
-        if count % 5 == 0 {
-            char.process(&input);
-            char.tick(&gfx);
+        controller.update();
+        input.update(pressed(&controller));
 
-            count = 0
-        }
-
-        count += 1;
+        char.process(&input);
+        char.tick(&gfx);
 
         gfx.commit();
     }
@@ -7,14 +7,20 @@ use agb::{
         Priority,
     },
     include_background_gfx,
-    input::{Button, ButtonController, Tri},
+    input::ButtonController,
     interrupt::VBlank,
     mgba::Mgba,
     Gba,
 };
 
+use logic::{
+    collision::check_boundary,
+    input::{Config, Input},
+};
+
 use crate::{
     background::LEVEL_MAP,
+    input::pressed,
     tiles::{CHAR_BACK, CHAR_FRONT, CHAR_LEFT, CHAR_RIGHT},
 };
 
@@ -22,7 +28,7 @@ include_background_gfx!(test_bg, tiles => 256 "gfx/test-bg.png");
 
 trait Entity<'a> {
     fn new(tag: &'static Tag, gfx: &'a OamManaged, coords: (i32, i32)) -> Self;
-    fn tick(&mut self, input: &ButtonController, gfx: &'a OamManaged);
+    fn tick(&mut self, input: &Input, gfx: &'a OamManaged);
 }
 
 struct Char<'a> {
@@ -45,35 +51,19 @@ impl<'a> Entity<'a> for Char<'a> {
         }
     }
 
-    fn tick(&mut self, input: &ButtonController, gfx: &'a OamManaged) {
-        let mut animate = false;
-
-        if input.is_pressed(Button::DOWN) {
-            self.current = CHAR_FRONT;
-            animate = true
-        }
-
-        if input.is_pressed(Button::UP) {
-            self.current = CHAR_BACK;
-            animate = true
-        }
-
-        if input.is_pressed(Button::RIGHT) {
-            self.current = CHAR_RIGHT;
-            animate = true
-        }
+    fn tick(&mut self, input: &Input, gfx: &'a OamManaged) {
+        let movement = input.movement();
 
-        if input.is_pressed(Button::LEFT) {
-            self.current = CHAR_LEFT;
-            animate = true
-        }
+        self.current = match movement {
+            (-1, _) => CHAR_LEFT,
+            (1, _) => CHAR_RIGHT,
+            (_, -1) => CHAR_BACK,
+            (_, 1) => CHAR_FRONT,
+            _ => self.current,
+        };
 
-        if animate {
-            self.current_idx = if self.current_idx > self.current.sprites().len() {
-                0
-            } else {
-                self.current_idx + 1
-            };
+        if movement != (0, 0) {
+            self.current_idx = (self.current_idx + 1) % self.current.sprites().len();
 
             self.sprite
                 .set_sprite(gfx.sprite(self.current.animation_sprite(self.current_idx)));
@@ -103,22 +93,16 @@ impl<'a> Entity<'a> for StaticObj<'a> {
         }
     }
 
-    fn tick(&mut self, input: &ButtonController, gfx: &'a OamManaged) {
-        match input.x_tri() {
-            Tri::Negative => self.coords.0 += 2,
-            Tri::Positive => self.coords.0 -= 2,
-            Tri::Zero => match input.y_tri() {
-                Tri::Negative => self.coords.1 += 2,
-                Tri::Positive => self.coords.1 -= 2,
-                Tri::Zero => {}
-            },
+    fn tick(&mut self, input: &Input, gfx: &'a OamManaged) {
+        match input.movement() {
+            (-1, _) => self.coords.0 += 2,
+            (1, _) => self.coords.0 -= 2,
+            (_, -1) => self.coords.1 += 2,
+            (_, 1) => self.coords.1 -= 2,
+            _ => {}
         }
 
-        self.current_idx = if self.current_idx > self.current.sprites().len() {
-            0
-        } else {
-            self.current_idx + 1
-        };
+        self.current_idx = (self.current_idx + 1) % self.current.sprites().len();
 
         self.sprite
             .set_sprite(gfx.sprite(self.current.animation_sprite(self.current_idx)));
@@ -159,7 +143,8 @@ pub fn run(mut gba: Gba) -> ! {
     let mut main_character = Char::new(CHAR_FRONT, &gfx, (104, 64));
 
     let vblank = VBlank::get();
-    let mut input = ButtonController::new();
+    let mut controller = ButtonController::new();
+    let mut input = Input::new(Config::default());
     let mut count = 0;
 
     let mut scroll_pos = (0, 0);
@@ -168,7 +153,8 @@ pub fn run(mut gba: Gba) -> ! {
         let _logger = logger.as_mut().unwrap();
 
         vblank.wait_for_vblank();
-        input.update();
+        controller.update();
+        input.update(pressed(&controller));
 
         if count % 5 == 0 {
             main_character.tick(&input, &gfx);
@@ -179,12 +165,10 @@ pub fn run(mut gba: Gba) -> ! {
             count = 0
         }
 
-        let new_scroll_pos = (
-            scroll_pos.0 + input.x_tri() as i16,
-            scroll_pos.1 + input.y_tri() as i16,
-        );
+        let (x, y) = input.movement();
+        let new_scroll_pos = (scroll_pos.0 + x as i16, scroll_pos.1 + y as i16);
 
-        let movement_blocked = check_boundary(new_scroll_pos, LEVEL_MAP);
+        let movement_blocked = check_boundary(new_scroll_pos, &LEVEL_MAP);
 
         if !movement_blocked {
             scroll_pos = new_scroll_pos;
@@ -195,16 +179,3 @@ pub fn run(mut gba: Gba) -> ! {
         gfx.commit();
     }
 }
-
-fn check_boundary((scroll_x, scroll_y): (i16, i16), level: [[usize; 64]; 32]) -> bool {
-    let x_offset = scroll_x / 8;
-    let y_offset = scroll_y / 8;
-
-    let right = level[(10 + y_offset) as usize][(15 + x_offset) as usize];
-    let left = level[(10 + y_offset) as usize][(14 + x_offset) as usize];
-
-    let top = level[(12 + y_offset) as usize][(15 + x_offset) as usize];
-    let bottom = level[(8 + y_offset) as usize][(15 + x_offset) as usize];
-
-    return right == 1 || left == 1 || top == 1 || bottom == 1;
-}
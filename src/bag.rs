@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+
+use agb::display::{
+    object::{OamManaged, Object, Sprite},
+    Priority,
+};
+
+use logic::{
+    input::Input,
+    menu::{Cursor, MenuEvent},
+};
+
+use crate::{
+    item::{Bag, Item},
+    tiles::{CURSOR, DIGIT_TAG, TIMES_TAG},
+};
+
+// Top left of the first row's icon, the rows go down the right of the screen.
+const ORIGIN: (i32, i32) = (184, 16);
+const ROW_HEIGHT: i32 = 18;
+
+// The items in the bag, an icon and a count per slot with a cursor beside the
+// chosen one. Drawn over the world on P0 until Cancel or Menu closes it.
+pub struct BagMenu<'a> {
+    cursor: Object<'a>,
+    // Kept so the sprites stay on screen while the menu is open.
+    rows: Vec<Object<'a>>,
+    slots: Vec<(Item, u8)>,
+    selected: Cursor,
+}
+
+impl<'a> BagMenu<'a> {
+    // None when there's nothing in the bag to show.
+    pub fn new(gfx: &'a OamManaged, bag: &Bag) -> Option<Self> {
+        let slots: Vec<(Item, u8)> = bag.slots().iter().flatten().copied().collect();
+        if slots.is_empty() {
+            return None;
+        }
+
+        let mut rows = Vec::new();
+        for (row, &(item, count)) in slots.iter().enumerate() {
+            let (x, y) = (ORIGIN.0, ORIGIN.1 + row as i32 * ROW_HEIGHT);
+
+            rows.push(shown(gfx, item.icon().sprite(0), (x, y)));
+            rows.push(shown(gfx, TIMES_TAG.sprite(0), (x + 16, y + 4)));
+            rows.push(shown(
+                gfx,
+                DIGIT_TAG.sprite(count as usize / 10),
+                (x + 24, y + 4),
+            ));
+            rows.push(shown(
+                gfx,
+                DIGIT_TAG.sprite(count as usize % 10),
+                (x + 31, y + 4),
+            ));
+        }
+
+        Some(Self {
+            cursor: shown(gfx, CURSOR.sprite(0), (ORIGIN.0 - 16, ORIGIN.1)),
+            rows,
+            selected: Cursor::new(slots.len()),
+            slots,
+        })
+    }
+
+    // How many sprites the menu has on screen.
+    pub fn objects(&self) -> usize {
+        self.rows.len() + 1
+    }
+
+    pub fn pointed(&self) -> (Item, u8) {
+        self.slots[self.selected.selected()]
+    }
+
+    // Nothing can be used yet, so choosing an item does nothing.
+    pub fn tick(&mut self, input: &mut Input) -> Option<MenuEvent> {
+        let event = self.selected.tick(input);
+
+        if let Some(MenuEvent::Moved(row)) = event {
+            self.cursor
+                .set_position((ORIGIN.0 - 16, ORIGIN.1 + row as i32 * ROW_HEIGHT));
+        }
+
+        event
+    }
+}
+
+fn shown<'a>(gfx: &'a OamManaged, sprite: &'static Sprite, position: (i32, i32)) -> Object<'a> {
+    let mut object = gfx.object_sprite(sprite);
+    object
+        .set_position(position)
+        .set_priority(Priority::P0)
+        .show();
+
+    object
+}
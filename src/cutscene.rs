@@ -0,0 +1,140 @@
+use agb::{display::tiled::VRamManager, mgba::Mgba};
+
+use logic::{
+    collision::body,
+    motion::Motion,
+    movement::MovementType,
+    script::{Actor, Fade, Script, Stage},
+    state::State,
+};
+use mapgen::generate_scripts;
+
+use crate::{
+    app::{log, scenery, FADE_FRAMES},
+    direction::Direction,
+    effects::{Effect, Effects, Tone},
+    item::Pickup,
+    map::Warp,
+    npc::Npc,
+    prop::Placed,
+    text::TextBox,
+};
+
+pub static SCRIPTS: &[Script] = &generate_scripts!("gfx/cutscenes.script");
+
+pub fn find(name: &str) -> &'static Script {
+    SCRIPTS
+        .iter()
+        .find(|script| script.name == name)
+        .unwrap_or_else(|| panic!("no script called {name}"))
+}
+
+// The bits of the main loop a cutscene gets to play with. Dialogue stays up
+// until the player presses A, and warps go through the usual fade out and in.
+pub struct Scene<'a, 'b, 'gba> {
+    pub motion: &'a mut Motion,
+    pub facing: &'a mut Direction,
+    pub npcs: &'a mut [Npc<'b>],
+    pub tilemap: &'a [[usize; 32]; 32],
+    pub props: &'a [Placed<'b>],
+    pub pickups: &'a [Pickup<'b>],
+    pub effects: &'a mut Effects<'gba>,
+    pub text: &'a mut TextBox<'b>,
+    pub vram: &'a mut VRamManager,
+    pub state: &'a mut State,
+    pub transition: &'a mut Option<Warp>,
+    pub logger: &'a mut Option<Mgba>,
+}
+
+impl<'b> Scene<'_, 'b, '_> {
+    // Scripts only know npcs by index, so one written for a different set of
+    // npcs gets logged and left out rather than taking the game down.
+    fn npc(&mut self, index: usize) -> Option<&mut Npc<'b>> {
+        let count = self.npcs.len();
+        let npc = self.npcs.get_mut(index);
+        if npc.is_none() {
+            log(
+                self.logger,
+                format_args!("script error: no npc {index}, the map has {count}"),
+            );
+        }
+
+        npc
+    }
+}
+
+impl Stage for Scene<'_, '_, '_> {
+    fn position(&mut self, actor: Actor) -> Option<(i32, i32)> {
+        match actor {
+            Actor::Player => {
+                let (x, y) = self.motion.pixel();
+                Some((x as i32, y as i32))
+            }
+            Actor::Npc(index) => self.npc(index).map(|npc| npc.position()),
+        }
+    }
+
+    fn place(&mut self, actor: Actor, (x, y): (i32, i32)) -> bool {
+        // The player goes wherever the script says, like after a warp.
+        let Actor::Npc(index) = actor else {
+            *self.motion = Motion::new((x as i16, y as i16));
+            return true;
+        };
+        if self.npc(index).is_none() {
+            return false;
+        }
+
+        let (px, py) = self.motion.pixel();
+        let player = body((px as i32, py as i32).into());
+        let (before, rest) = self.npcs.split_at_mut(index);
+        let (npc, after) = rest.split_first_mut().unwrap();
+
+        npc.place((x, y), self.tilemap, |area| {
+            area.touches(player)
+                || scenery(area, self.props, self.pickups, self.state)
+                || before
+                    .iter()
+                    .chain(after.iter())
+                    .any(|other| other.body().touches(area))
+        })
+    }
+
+    fn face(&mut self, actor: Actor, facing: MovementType) {
+        match actor {
+            Actor::Player => *self.facing = facing.into(),
+            Actor::Npc(index) => {
+                if let Some(npc) = self.npc(index) {
+                    npc.face(facing.into());
+                }
+            }
+        }
+    }
+
+    fn say(&mut self, text: &'static str) {
+        log(self.logger, format_args!("{text}"));
+        self.text.show(self.vram, text);
+    }
+
+    fn state(&mut self) -> &mut State {
+        self.state
+    }
+
+    fn fade(&mut self, fade: Fade, frames: u16) {
+        let effect = match fade {
+            Fade::Out => Effect::FadeOut(Tone::Black),
+            Fade::In => Effect::FadeIn(Tone::Black),
+        };
+
+        self.effects.start(effect, frames);
+    }
+
+    fn warp(&mut self, map: &'static str, spawn: (i32, i32)) {
+        *self.transition = Some(Warp { map, spawn });
+        self.effects
+            .start(Effect::FadeOut(Tone::Black), FADE_FRAMES);
+    }
+
+    fn busy(&self) -> bool {
+        self.text.is_showing() || self.effects.fading() || self.transition.is_some()
+    }
+}
@@ -0,0 +1,241 @@
+use core::ptr;
+
+use alloc::vec::Vec;
+
+use agb::display::{
+    object::{DynamicSprite, Graphics, PaletteVram, Sprite, SpriteVram},
+    palette16::Palette16,
+    tiled::VRamManager,
+};
+
+const COLOURS: usize = 256;
+
+// A copy of each file of world sprites, see build.rs.
+const SHEETS: &[Sheet] = include!(concat!(env!("OUT_DIR"), "/world_sprites.rs"));
+
+const FRAMES_PER_MINUTE: u32 = 30;
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+// Colours written per frame, a full pass over the palettes takes 8 frames.
+const CHUNK: usize = 32;
+
+// Minute of the day and the red, green, blue multipliers (out of 16) at that time,
+// tints in between are interpolated.
+const KEYFRAMES: [(u16, [u16; 3]); 7] = [
+    (0, [6, 7, 12]),
+    (5 * 60, [6, 7, 12]),
+    (6 * 60 + 30, [15, 12, 11]),
+    (8 * 60, [16, 16, 16]),
+    (17 * 60, [16, 16, 16]),
+    (19 * 60, [16, 10, 8]),
+    (21 * 60, [6, 7, 12]),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+pub struct Clock {
+    minutes: u16,
+    frames: u32,
+}
+
+impl Clock {
+    pub fn new(minutes: u16) -> Self {
+        Self {
+            minutes: minutes % MINUTES_PER_DAY,
+            frames: 0,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.frames += 1;
+
+        if self.frames >= FRAMES_PER_MINUTE {
+            self.frames = 0;
+            self.minutes = (self.minutes + 1) % MINUTES_PER_DAY;
+        }
+    }
+
+    pub fn minutes(&self) -> u16 {
+        self.minutes
+    }
+
+    pub fn phase(&self) -> Phase {
+        match self.minutes / 60 {
+            5..=7 => Phase::Dawn,
+            8..=16 => Phase::Day,
+            17..=20 => Phase::Dusk,
+            _ => Phase::Night,
+        }
+    }
+
+    fn tint(&self) -> [u16; 3] {
+        let next = KEYFRAMES
+            .iter()
+            .position(|&(minute, _)| minute > self.minutes)
+            .unwrap_or(KEYFRAMES.len());
+
+        let (from_minute, from) = KEYFRAMES[next - 1];
+        let (to_minute, to) = KEYFRAMES
+            .get(next)
+            .copied()
+            .unwrap_or((MINUTES_PER_DAY, KEYFRAMES[0].1));
+
+        let progress = (self.minutes - from_minute) as i32;
+        let length = (to_minute - from_minute) as i32;
+
+        let mut tint = [0; 3];
+        for channel in 0..3 {
+            let delta = to[channel] as i32 - from[channel] as i32;
+            tint[channel] = (from[channel] as i32 + delta * progress / length) as u16;
+        }
+
+        tint
+    }
+}
+
+fn apply_tint(colour: u16, tint: [u16; 3]) -> u16 {
+    let mut tinted = 0;
+
+    for (channel, multiplier) in tint.iter().enumerate() {
+        let value = (colour >> (channel * 5)) & 0x1f;
+        tinted |= ((value * multiplier / 16).min(0x1f)) << (channel * 5);
+    }
+
+    tinted
+}
+
+pub struct DayNight {
+    pub clock: Clock,
+    background: &'static [Palette16],
+    tint: [u16; 3],
+    cursor: usize,
+}
+
+impl DayNight {
+    pub fn new(background: &'static [Palette16], clock: Clock) -> Self {
+        Self {
+            clock,
+            background,
+            tint: [16; 3],
+            cursor: 0,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.clock.phase()
+    }
+
+    // For anything else writing background colours, like palette cycles, so
+    // they match the rest of the palette.
+    pub fn tint(&self, colour: u16) -> u16 {
+        apply_tint(colour, self.tint)
+    }
+
+    // The red, green and blue multipliers the world is tinted by, for
+    // WorldSprites.
+    pub fn multipliers(&self) -> [u16; 3] {
+        self.tint
+    }
+
+    // Call once per frame during vblank, only touches a slice of the palettes
+    // each time so the change in tint spreads over several frames.
+    pub fn tick(&mut self, vram: &mut VRamManager) {
+        self.clock.tick();
+
+        if self.cursor == 0 {
+            self.tint = self.clock.tint();
+        }
+
+        for index in self.cursor..self.cursor + CHUNK {
+            let (palette, colour) = (index / 16, index % 16);
+
+            if let Some(base) = self.background.get(palette) {
+                let tinted = apply_tint(base.colour(colour), self.tint);
+                vram.set_background_palette_colour(palette, colour, tinted);
+            }
+        }
+
+        self.cursor = (self.cursor + CHUNK) % COLOURS;
+    }
+}
+
+// One file of world sprites. The frames are in the same order as
+// `graphics.sprites()`, with a palette index for each pixel.
+struct Sheet {
+    graphics: &'static Graphics,
+    palette: Palette16,
+    frames: &'static [&'static [u8]],
+}
+
+// Loads world sprites with their palette tinted. agb only copies a sprite's
+// palette into vram along with the first sprite using it, so each one is
+// rebuilt from the copy in SHEETS when the tint changes. The old palette goes
+// once nothing shows a sprite using it.
+pub struct WorldSprites {
+    tint: [u16; 3],
+    // One for each sheet, loaded with the first of its sprites.
+    palettes: Vec<Option<PaletteVram>>,
+    loaded: Vec<(&'static Sprite, SpriteVram)>,
+}
+
+impl WorldSprites {
+    pub fn new() -> Self {
+        Self {
+            tint: [16; 3],
+            palettes: SHEETS.iter().map(|_| None).collect(),
+            loaded: Vec::new(),
+        }
+    }
+
+    // Sprites loaded after this get the new tint.
+    pub fn set_tint(&mut self, tint: [u16; 3]) {
+        if tint != self.tint {
+            self.tint = tint;
+            self.palettes.fill(None);
+            self.loaded.clear();
+        }
+    }
+
+    // None for a sprite that isn't part of the world.
+    pub fn load(&mut self, sprite: &'static Sprite) -> Option<SpriteVram> {
+        if let Some((_, loaded)) = self.loaded.iter().find(|(seen, _)| ptr::eq(*seen, sprite)) {
+            return Some(loaded.clone());
+        }
+
+        let (index, sheet, frame) = SHEETS.iter().enumerate().find_map(|(index, sheet)| {
+            let sprites = sheet.graphics.sprites();
+            let frame = sprites.iter().position(|each| ptr::eq(each, sprite))?;
+
+            Some((index, sheet, frame))
+        })?;
+
+        let tint = self.tint;
+        let palette = self.palettes[index].get_or_insert_with(|| {
+            let mut tinted = sheet.palette.clone();
+            for colour in 1..16 {
+                tinted.update_colour(colour, apply_tint(tinted.colour(colour), tint));
+            }
+
+            PaletteVram::new(&tinted).expect("no room for a world sprite palette")
+        });
+
+        let (width, _) = sprite.size().to_width_height();
+        let mut pixels = DynamicSprite::new(sprite.size());
+        for (offset, &colour) in sheet.frames[frame].iter().enumerate() {
+            if colour != 0 {
+                pixels.set_pixel(offset % width, offset / width, colour as usize);
+            }
+        }
+
+        let loaded = pixels.to_vram(palette.clone());
+        self.loaded.push((sprite, loaded.clone()));
+
+        Some(loaded)
+    }
+}
@@ -0,0 +1,25 @@
+use agb::{
+    display::{object::Object, Priority},
+    fixnum::Rect,
+};
+
+use logic::depth::{z, Layer};
+
+// The parts of stamped props that hang over whatever walks behind them, like
+// roofs. P0 stays free for anything drawn over the world.
+pub const OVERHANG: Priority = Priority::P1;
+
+// Every sprite standing on the map. The GBA orders sprites by priority before
+// OAM position, so they all have to share one for the y sort to hold.
+pub const WORLD: Priority = Priority::P2;
+
+// The map itself, with the rest of each stamped prop drawn into it. Sprites
+// always draw over a background of the same or lower priority, so anything
+// walking in front of a stamp is drawn over it.
+pub const GROUND: Priority = Priority::P3;
+
+// OamManaged hands out the 128 OAM slots to visible sprites in z order, so if
+// there are ever too many it's the ones furthest back that miss out.
+pub fn sort(sprite: &mut Object, body: Rect<i32>, layer: Layer) {
+    sprite.set_z(z(body, layer)).set_priority(WORLD);
+}
@@ -0,0 +1,72 @@
+use agb::{display::object::Tag, fixnum::Vector2D};
+
+use logic::{input::Input, movement::MovementType};
+
+use crate::tiles::{CHAR_BACK, CHAR_FRONT, CHAR_LEFT, CHAR_RIGHT};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn from_input(input: &Input) -> Option<Self> {
+        match input.movement() {
+            (1, _) => Some(Direction::Right),
+            (-1, _) => Some(Direction::Left),
+            (_, 1) => Some(Direction::Down),
+            (_, -1) => Some(Direction::Up),
+            _ => None,
+        }
+    }
+
+    // Direction to look at `to` when standing at `from`, the dominant axis wins.
+    pub fn towards(from: Vector2D<i32>, to: Vector2D<i32>) -> Self {
+        let delta = to - from;
+
+        if delta.x.abs() > delta.y.abs() {
+            if delta.x > 0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if delta.y > 0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    pub fn offset(self) -> Vector2D<i32> {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+        .into()
+    }
+
+    pub fn tag(self) -> &'static Tag {
+        match self {
+            Direction::Up => CHAR_BACK,
+            Direction::Down => CHAR_FRONT,
+            Direction::Left => CHAR_LEFT,
+            Direction::Right => CHAR_RIGHT,
+        }
+    }
+}
+
+impl From<MovementType> for Direction {
+    fn from(tp: MovementType) -> Self {
+        match tp {
+            MovementType::Up => Direction::Up,
+            MovementType::Down => Direction::Down,
+            MovementType::Left => Direction::Left,
+            MovementType::Right => Direction::Right,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use alloc::vec::Vec;
+
+use agb::{
+    display::{
+        blend::{Blend, BlendMode, Layer},
+        palette16::Palette16,
+        tiled::{BackgroundID, VRamManager},
+    },
+    fixnum::Num,
+};
+
+const FULL: u8 = 16;
+const FLASH_PERIOD: u16 = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Black,
+    White,
+}
+
+#[derive(Clone, Copy)]
+pub enum Effect {
+    FadeOut(Tone),
+    FadeIn(Tone),
+    // Blinks the sprites white, for damage feedback.
+    Flash,
+    // Rotates `colours` once every `period` frames through whichever slots of
+    // the background `palettes` hold them, for water and the like. Where they end
+    // up is up to the quantiser, so they're found by colour. The colours are
+    // written every frame so nothing else writing the palette can leave them
+    // behind.
+    PaletteCycle {
+        palettes: &'static [Palette16],
+        colours: &'static [u16],
+        period: u16,
+    },
+}
+
+struct Task {
+    effect: Effect,
+    // How long the task runs for, 0 keeps it going until it is stopped.
+    frames: u16,
+    elapsed: u16,
+}
+
+impl Task {
+    fn done(&self) -> bool {
+        self.frames != 0 && self.elapsed >= self.frames
+    }
+}
+
+pub struct Effects<'gba> {
+    blend: Blend<'gba>,
+    backgrounds: Vec<BackgroundID>,
+    tasks: Vec<Task>,
+    tone: Tone,
+    level: u8,
+}
+
+impl<'gba> Effects<'gba> {
+    pub fn new(blend: Blend<'gba>, backgrounds: &[BackgroundID]) -> Self {
+        Self {
+            blend,
+            backgrounds: backgrounds.to_vec(),
+            tasks: Vec::new(),
+            tone: Tone::Black,
+            level: 0,
+        }
+    }
+
+    pub fn start(&mut self, effect: Effect, frames: u16) {
+        if let Effect::FadeOut(_) | Effect::FadeIn(_) = effect {
+            self.tasks
+                .retain(|task| !matches!(task.effect, Effect::FadeOut(_) | Effect::FadeIn(_)));
+        }
+
+        self.tasks.push(Task {
+            effect,
+            frames,
+            elapsed: 0,
+        });
+    }
+
+    pub fn fading(&self) -> bool {
+        self.tasks
+            .iter()
+            .any(|task| matches!(task.effect, Effect::FadeOut(_) | Effect::FadeIn(_)))
+    }
+
+    // Call once per frame, straight after vblank. Cycled colours go through
+    // `tint` on the way out, for day and night.
+    pub fn tick(&mut self, vram: &mut VRamManager, tint: impl Fn(u16) -> u16) {
+        let mut flashing = false;
+
+        for task in self.tasks.iter_mut() {
+            let progress = if task.frames == 0 {
+                FULL
+            } else {
+                ((task.elapsed + 1) * FULL as u16 / task.frames).min(FULL as u16) as u8
+            };
+
+            match task.effect {
+                Effect::FadeOut(tone) => {
+                    self.tone = tone;
+                    self.level = progress;
+                }
+                Effect::FadeIn(tone) => {
+                    self.tone = tone;
+                    self.level = FULL - progress;
+                }
+                Effect::Flash => flashing = (task.elapsed / FLASH_PERIOD).is_multiple_of(2),
+                Effect::PaletteCycle {
+                    palettes,
+                    colours,
+                    period,
+                } => {
+                    let offset = (task.elapsed / period.max(1)) as usize;
+
+                    // The first colour is transparent.
+                    for (palette, base) in palettes.iter().enumerate() {
+                        for slot in 1..16 {
+                            let Some(i) = colours.iter().position(|&c| c == base.colour(slot))
+                            else {
+                                continue;
+                            };
+
+                            let colour = colours[(i + offset) % colours.len()];
+                            vram.set_background_palette_colour(palette, slot, tint(colour));
+                        }
+                    }
+                }
+            }
+
+            task.elapsed = task.elapsed.wrapping_add(1);
+        }
+
+        self.tasks.retain(|task| !task.done());
+        self.apply(flashing);
+    }
+
+    fn apply(&mut self, flashing: bool) {
+        self.blend.reset();
+
+        if self.level > 0 {
+            let mode = match self.tone {
+                Tone::Black => BlendMode::FadeToBlack,
+                Tone::White => BlendMode::FadeToWhite,
+            };
+
+            let mut layer = self.blend.layer(Layer::Top);
+            for &background in self.backgrounds.iter() {
+                layer.set_background_enable(background, true);
+            }
+            layer.set_object_enable(true).set_backdrop_enable(true);
+
+            self.blend
+                .set_blend_mode(mode)
+                .set_fade(Num::from_raw(self.level));
+        } else if flashing {
+            self.blend
+                .set_blend_mode(BlendMode::FadeToWhite)
+                .set_fade(Num::from_raw(FULL))
+                .layer(Layer::Top)
+                .set_object_enable(true);
+        } else {
+            self.blend.set_blend_mode(BlendMode::Off);
+        }
+
+        self.blend.commit();
+    }
+}
@@ -0,0 +1,21 @@
+use agb::input::{Button, ButtonController};
+
+// The buttons held this frame as a mask for logic::input, which takes care of
+// actions, buffering and the player's button config.
+pub fn pressed(controller: &ButtonController) -> u16 {
+    [
+        Button::A,
+        Button::B,
+        Button::SELECT,
+        Button::START,
+        Button::RIGHT,
+        Button::LEFT,
+        Button::UP,
+        Button::DOWN,
+        Button::R,
+        Button::L,
+    ]
+    .into_iter()
+    .filter(|&button| controller.is_pressed(button))
+    .fold(0, |mask, button| mask | button.bits() as u16)
+}
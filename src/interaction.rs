@@ -0,0 +1,116 @@
+use agb::fixnum::{Rect, Vector2D};
+
+use logic::{
+    collision::{center, BODY_SIZE},
+    map::TILE_SIZE,
+    state::{Condition, Flag, State},
+};
+
+use crate::{
+    direction::Direction,
+    item::{Bag, Item},
+    map::Warp,
+};
+
+// How far past the centre of the body the player can reach, ends up half a tile
+// into whatever stands directly in front of them.
+const REACH: i32 = BODY_SIZE.0 / 2 + TILE_SIZE / 2;
+
+pub enum ObjectKind {
+    Sign(&'static str),
+    // The flag is set once it's been opened.
+    Chest(Item, Flag),
+    // Lying on the ground until picked up, which sets the flag.
+    Item(Item, Flag),
+    // Locked unless the condition holds.
+    Door(Warp, Condition),
+    // Fires as soon as the player steps on it rather than on A.
+    Warp(Warp),
+}
+
+pub struct MapObject {
+    pub kind: ObjectKind,
+    pub tile: (i32, i32),
+}
+
+// What a map's trigger zones do, see the .triggers files.
+pub enum Trigger {
+    Cutscene(&'static str),
+    AreaName(&'static str),
+    Encounter(&'static str),
+}
+
+pub enum Event {
+    Read(&'static str),
+    Found(Item),
+    // The item didn't fit in the bag, so it's left where it was.
+    Full(Item),
+    Empty,
+    Locked,
+    Enter(Warp),
+}
+
+pub struct Interactions {
+    objects: &'static [MapObject],
+}
+
+impl Interactions {
+    pub fn new(objects: &'static [MapObject]) -> Self {
+        Self { objects }
+    }
+
+    pub fn at(&self, tile: (i32, i32), state: &State) -> Option<usize> {
+        self.objects.iter().position(|object| {
+            object.tile == tile
+                && match object.kind {
+                    ObjectKind::Warp(_) => false,
+                    ObjectKind::Item(_, taken) => !state.flag(taken),
+                    _ => true,
+                }
+        })
+    }
+
+    pub fn pickups(&self) -> impl Iterator<Item = (Item, Flag, (i32, i32))> + '_ {
+        self.objects.iter().filter_map(|object| match object.kind {
+            ObjectKind::Item(item, taken) => Some((item, taken, object.tile)),
+            _ => None,
+        })
+    }
+
+    pub fn warp_touching(&self, area: Rect<i32>) -> Option<Warp> {
+        self.objects.iter().find_map(|object| match object.kind {
+            ObjectKind::Warp(warp) if tile_rect(object.tile).touches(area) => Some(warp),
+            _ => None,
+        })
+    }
+
+    pub fn trigger(&self, index: usize, state: &mut State, bag: &mut Bag) -> Event {
+        match self.objects[index].kind {
+            ObjectKind::Sign(text) => Event::Read(text),
+            ObjectKind::Chest(_, flag) | ObjectKind::Item(_, flag) if state.flag(flag) => {
+                Event::Empty
+            }
+            ObjectKind::Chest(item, flag) | ObjectKind::Item(item, flag) => {
+                if !bag.add(item, 1) {
+                    return Event::Full(item);
+                }
+
+                state.set(flag, true);
+                Event::Found(item)
+            }
+            ObjectKind::Door(_, condition) if !state.check(condition) => Event::Locked,
+            ObjectKind::Door(warp, _) | ObjectKind::Warp(warp) => Event::Enter(warp),
+        }
+    }
+}
+
+fn tile_rect((x, y): (i32, i32)) -> Rect<i32> {
+    Rect::new(
+        (x * TILE_SIZE, y * TILE_SIZE).into(),
+        (TILE_SIZE, TILE_SIZE).into(),
+    )
+}
+
+pub fn ahead(body: Rect<i32>, facing: Direction) -> Vector2D<i32> {
+    center(body) + facing.offset() * REACH
+}
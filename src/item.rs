@@ -0,0 +1,77 @@
+use agb::{
+    display::object::{OamManaged, Object, Tag},
+    fixnum::{Rect, Vector2D},
+};
+
+use logic::{depth::Layer, inventory::Inventory, map::TILE_SIZE, state::Flag};
+
+use crate::{depth, oam::Budget, tiles::ITEMS};
+
+pub const BAG_SLOTS: usize = 8;
+
+pub type Bag = Inventory<Item, BAG_SLOTS>;
+
+// Each item's icon is the tag of the same name in items.aseprite.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Item {
+    Potion,
+    Apple,
+    Key,
+}
+
+impl Item {
+    // In save order, new items go at the end.
+    pub const ALL: [Item; 3] = [Item::Potion, Item::Apple, Item::Key];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Item::Potion => "Potion",
+            Item::Apple => "Apple",
+            Item::Key => "Key",
+        }
+    }
+
+    pub fn icon(self) -> &'static Tag {
+        ITEMS.tags().get(self.name())
+    }
+}
+
+// An item lying on a map tile until it's picked up, which sets `taken`. It
+// blocks its tile so the player has to face it to pick it up.
+pub struct Pickup<'a> {
+    sprite: Object<'a>,
+    item: Item,
+    pub taken: Flag,
+    footprint: Rect<i32>,
+}
+
+impl<'a> Pickup<'a> {
+    pub fn new(gfx: &'a OamManaged, item: Item, taken: Flag, (x, y): (i32, i32)) -> Self {
+        Self {
+            sprite: gfx.object_sprite(item.icon().sprite(0)),
+            item,
+            taken,
+            footprint: Rect::new(
+                (x * TILE_SIZE, y * TILE_SIZE).into(),
+                (TILE_SIZE, TILE_SIZE).into(),
+            ),
+        }
+    }
+
+    pub fn footprint(&self) -> Rect<i32> {
+        self.footprint
+    }
+
+    pub fn render(&mut self, scroll: Vector2D<i32>, budget: &mut Budget, taken: bool) {
+        if taken {
+            self.sprite.hide();
+            return;
+        }
+
+        // 16x16 icons sit centred on their 8x8 tile.
+        let position = self.footprint.position - (4, 4).into() - scroll;
+        if budget.show(&mut self.sprite, self.item.icon().sprite(0), position) {
+            depth::sort(&mut self.sprite, self.footprint, Layer::Prop);
+        }
+    }
+}
@@ -4,6 +4,8 @@
 #![cfg_attr(test, reexport_test_harness_main = "test_main")]
 #![cfg_attr(test, test_runner(agb::test_runner::test_runner))]
 
+extern crate alloc;
+
 use app::run;
 // use app_ret::run;
 
@@ -11,7 +13,28 @@ mod app;
 mod app_bc;
 mod app_ret;
 mod background;
+mod bag;
+mod cutscene;
+mod daynight;
+mod depth;
+mod direction;
+mod effects;
+mod input;
+mod interaction;
+mod item;
+mod map;
+mod npc;
+mod oam;
+mod options;
+mod pause;
+mod prop;
+mod save;
+mod sound;
+mod stamp;
+mod state;
+mod text;
 mod tiles;
+mod title;
 
 #[agb::entry]
 fn main(mut gba: agb::Gba) -> ! {
@@ -0,0 +1,71 @@
+use logic::{metatile::MetatileMap, trigger::Zone};
+use mapgen::{
+    generate_background_map, generate_map_npcs, generate_map_objects, generate_map_props,
+    generate_map_triggers, generate_metatile_map,
+};
+
+use crate::{
+    interaction::{MapObject, Trigger},
+    npc::Behaviour,
+    prop::Prop,
+};
+
+#[derive(Clone, Copy)]
+pub struct Warp {
+    pub map: &'static str,
+    pub spawn: (i32, i32),
+}
+
+// Maps whose walls line up with a 2x2 grid can be authored in metatiles, which
+// is a fraction of the size in ROM. The rest stay as plain tiles.
+pub enum Layout {
+    Tiles(&'static [[usize; 32]; 32]),
+    Metatiles(MetatileMap),
+}
+
+pub struct Map {
+    pub name: &'static str,
+    pub layout: Layout,
+    pub objects: &'static [MapObject],
+    pub npcs: &'static [((i32, i32), Behaviour, &'static str)],
+    pub props: &'static [Prop],
+    pub triggers: &'static [Zone<Trigger>],
+}
+
+pub static MAPS: &[Map] = &[
+    Map {
+        name: "bg",
+        layout: Layout::Tiles(&generate_background_map!("gfx/bg.png", "gfx/bg-tiles.png")),
+        objects: &generate_map_objects!("gfx/bg.objects"),
+        npcs: &generate_map_npcs!("gfx/bg.npcs"),
+        props: &generate_map_props!("gfx/bg.props"),
+        triggers: &generate_map_triggers!("gfx/bg.triggers"),
+    },
+    Map {
+        name: "house-1",
+        layout: Layout::Metatiles(generate_metatile_map!(
+            "gfx/house-1.png",
+            "gfx/bg-tiles.png",
+            2
+        )),
+        objects: &generate_map_objects!("gfx/house-1.objects"),
+        npcs: &[],
+        props: &[],
+        triggers: &generate_map_triggers!("gfx/house-1.triggers"),
+    },
+];
+
+impl Map {
+    pub fn tilemap(&self) -> [[usize; 32]; 32] {
+        match &self.layout {
+            Layout::Tiles(tilemap) => **tilemap,
+            Layout::Metatiles(metatiles) => metatiles.expand(),
+        }
+    }
+}
+
+pub fn find(name: &str) -> &'static Map {
+    MAPS.iter()
+        .find(|map| map.name == name)
+        .unwrap_or_else(|| panic!("no map called {name}"))
+}
@@ -0,0 +1,184 @@
+use agb::{
+    display::object::{OamManaged, Object},
+    fixnum::{Rect, Vector2D},
+    rng,
+};
+
+use logic::{
+    collision::{body, center},
+    depth::Layer,
+    map::area_walkable,
+};
+
+use crate::{depth, direction::Direction, oam::Budget};
+
+const WANDER_PAUSE: i32 = 90;
+const BLOCKED_PAUSE: u16 = 30;
+const TALK_PAUSE: u16 = 120;
+const FRAME_STEP: u32 = 8;
+
+#[derive(Clone, Copy)]
+pub enum Behaviour {
+    Still,
+    Wander { radius: i32 },
+    Patrol(&'static [(i32, i32)]),
+}
+
+pub struct Npc<'a> {
+    sprite: Object<'a>,
+    facing: Direction,
+    frame: usize,
+    steps: u32,
+    position: Vector2D<i32>,
+    home: Vector2D<i32>,
+    behaviour: Behaviour,
+    target: Option<Vector2D<i32>>,
+    waypoint: usize,
+    wait: u16,
+    pub text: &'static str,
+}
+
+impl<'a> Npc<'a> {
+    pub fn new(
+        gfx: &'a OamManaged,
+        (x, y): (i32, i32),
+        behaviour: Behaviour,
+        text: &'static str,
+    ) -> Self {
+        let facing = Direction::Down;
+
+        Self {
+            sprite: gfx.object_sprite(facing.tag().animation_sprite(0)),
+            facing,
+            frame: 0,
+            steps: 0,
+            position: (x, y).into(),
+            home: (x, y).into(),
+            behaviour,
+            target: None,
+            waypoint: 0,
+            wait: 0,
+            text,
+        }
+    }
+
+    pub fn body(&self) -> Rect<i32> {
+        body(self.position)
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        (self.position.x, self.position.y)
+    }
+
+    // Moved by a cutscene, which walks it wherever regardless of its behaviour
+    // but not through walls or anything `occupied`. False if it couldn't go.
+    pub fn place<const W: usize, const H: usize>(
+        &mut self,
+        (x, y): (i32, i32),
+        tilemap: &[[usize; W]; H],
+        occupied: impl Fn(Rect<i32>) -> bool,
+    ) -> bool {
+        let position = (x, y).into();
+        if !area_walkable(body(position), tilemap) || occupied(body(position)) {
+            return false;
+        }
+
+        self.step_to(position);
+        true
+    }
+
+    pub fn face(&mut self, facing: Direction) {
+        self.facing = facing;
+    }
+
+    pub fn talk_to(&mut self, other: Rect<i32>) {
+        self.facing = Direction::towards(center(self.body()), center(other));
+        self.frame = 0;
+        self.wait = TALK_PAUSE;
+    }
+
+    pub fn tick<const W: usize, const H: usize>(
+        &mut self,
+        tilemap: &[[usize; W]; H],
+        occupied: impl Fn(Rect<i32>) -> bool,
+    ) {
+        if self.wait > 0 {
+            self.wait -= 1;
+        } else if let Some(target) = self.next_target().filter(|&t| t != self.position) {
+            let delta = target - self.position;
+
+            self.facing = if delta.x != 0 {
+                Direction::towards((0, 0).into(), (delta.x, 0).into())
+            } else {
+                Direction::towards((0, 0).into(), (0, delta.y).into())
+            };
+
+            let next = self.position + self.facing.offset();
+
+            if area_walkable(body(next), tilemap) && !occupied(body(next)) {
+                self.step_to(next);
+            } else {
+                if let Behaviour::Wander { .. } = self.behaviour {
+                    self.target = None;
+                }
+
+                self.wait = BLOCKED_PAUSE;
+            }
+        }
+    }
+
+    pub fn render(&mut self, scroll: Vector2D<i32>, budget: &mut Budget) {
+        let sprite = self.facing.tag().animation_sprite(self.frame);
+        let body = self.body();
+
+        if budget.show(&mut self.sprite, sprite, self.position - scroll) {
+            depth::sort(&mut self.sprite, body, Layer::Npc);
+        }
+    }
+
+    fn step_to(&mut self, position: Vector2D<i32>) {
+        self.position = position;
+        self.steps += 1;
+
+        if self.steps.is_multiple_of(FRAME_STEP) {
+            self.frame = (self.frame + 1) % self.facing.tag().sprites().len();
+        }
+    }
+
+    fn next_target(&mut self) -> Option<Vector2D<i32>> {
+        match self.behaviour {
+            Behaviour::Still => None,
+            Behaviour::Wander { radius } => match self.target {
+                Some(target) if target == self.position => {
+                    self.target = None;
+                    self.wait = rng::gen().rem_euclid(WANDER_PAUSE) as u16;
+
+                    None
+                }
+                Some(target) => Some(target),
+                None => {
+                    let offset = rng::gen().rem_euclid(radius * 2 + 1) - radius;
+                    let offset = if rng::gen() % 2 == 0 {
+                        (offset, 0)
+                    } else {
+                        (0, offset)
+                    };
+
+                    self.target = Some(self.home + offset.into());
+                    self.target
+                }
+            },
+            Behaviour::Patrol(path) => {
+                if path.is_empty() {
+                    return None;
+                }
+
+                if Vector2D::from(path[self.waypoint]) == self.position {
+                    self.waypoint = (self.waypoint + 1) % path.len();
+                }
+
+                Some(path[self.waypoint].into())
+            }
+        }
+    }
+}
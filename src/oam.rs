@@ -0,0 +1,114 @@
+use core::ptr;
+
+use alloc::vec::Vec;
+
+use agb::{
+    display::object::{OamManaged, Object, Sprite, SpriteVram},
+    fixnum::Vector2D,
+};
+
+use logic::oam::{Slots, OBJECTS, SPRITE_TILES};
+
+use crate::daynight::WorldSprites;
+
+const SCREEN_SIZE: (i32, i32) = (240, 160);
+
+// Kept back for the player and anything drawn over the world.
+const RESERVED: usize = 8;
+
+pub struct Overrun {
+    pub objects: usize,
+    pub tiles: usize,
+}
+
+// Sits between the world sprites and OamManaged, which would otherwise quietly
+// drop whatever doesn't fit. Sprites off camera are hidden, the rest take turns
+// if there are too many, and going over either budget is reported once. It
+// also loads them tinted for the time of day.
+pub struct Budget<'a> {
+    gfx: &'a OamManaged<'a>,
+    world: WorldSprites,
+    slots: Slots,
+    sprites: Vec<&'static Sprite>,
+    tiles: usize,
+    over: bool,
+}
+
+impl<'a> Budget<'a> {
+    pub fn new(gfx: &'a OamManaged<'a>) -> Self {
+        Self {
+            gfx,
+            world: WorldSprites::new(),
+            slots: Slots::new(OBJECTS - RESERVED),
+            sprites: Vec::new(),
+            tiles: 0,
+            over: false,
+        }
+    }
+
+    // Holds back slots this frame for sprites drawn over the world that don't
+    // go through the budget, like an open menu.
+    pub fn reserve(&mut self, objects: usize) {
+        self.slots.reserve(objects);
+    }
+
+    // See DayNight::multipliers.
+    pub fn tint(&mut self, multipliers: [u16; 3]) {
+        self.world.set_tint(multipliers);
+    }
+
+    // `sprite` as it should look right now, for sprites that don't go through
+    // the budget.
+    pub fn load(&mut self, sprite: &'static Sprite) -> SpriteVram {
+        self.world
+            .load(sprite)
+            .unwrap_or_else(|| self.gfx.sprite(sprite))
+    }
+
+    // Shows `object` as `sprite` at `position` on screen if it's in view and gets
+    // a slot this frame, hiding it otherwise. Returns whether it's shown.
+    pub fn show(
+        &mut self,
+        object: &mut Object,
+        sprite: &'static Sprite,
+        position: Vector2D<i32>,
+    ) -> bool {
+        let (width, height) = sprite.size().to_width_height();
+
+        // Even when hidden, so nothing hangs on to a palette from an old tint.
+        object.set_sprite(self.load(sprite));
+
+        // Hidden sprites hang on to their tiles, so count them either way.
+        if !self.sprites.iter().any(|&seen| ptr::eq(seen, sprite)) {
+            self.sprites.push(sprite);
+            self.tiles += width * height / 64;
+        }
+
+        let in_view = position.x > -(width as i32)
+            && position.y > -(height as i32)
+            && position.x < SCREEN_SIZE.0
+            && position.y < SCREEN_SIZE.1;
+
+        if !in_view || !self.slots.request() {
+            object.hide();
+            return false;
+        }
+
+        object.set_position(position).show();
+        true
+    }
+
+    // Call once all the world sprites have been shown for the frame.
+    pub fn finish(&mut self) -> Option<Overrun> {
+        let capacity = self.slots.capacity();
+        let objects = self.slots.finish();
+        let tiles = core::mem::take(&mut self.tiles);
+        self.sprites.clear();
+
+        let over = objects > capacity || tiles > SPRITE_TILES;
+        let newly_over = over && !self.over;
+        self.over = over;
+
+        newly_over.then_some(Overrun { objects, tiles })
+    }
+}
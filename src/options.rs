@@ -0,0 +1,140 @@
+use alloc::{format, string::String, vec::Vec};
+
+use agb::display::tiled::VRamManager;
+
+use logic::{
+    input::{button, Action, Config, Diagonal, Input},
+    menu::{Cursor, MenuEvent},
+};
+
+use crate::text::TextBox;
+
+// What an action can be bound to, and what to call it. The d-pad is left out,
+// it's always for moving.
+const BUTTONS: [(u16, &str); 6] = [
+    (button::A, "A"),
+    (button::B, "B"),
+    (button::L, "L"),
+    (button::R, "R"),
+    (button::START, "Start"),
+    (button::SELECT, "Select"),
+];
+
+// In the order they're listed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Row {
+    Diagonal,
+    Bind(Action),
+}
+
+impl Row {
+    pub const ALL: [Row; 6] = [
+        Row::Diagonal,
+        Row::Bind(Action::Confirm),
+        Row::Bind(Action::Cancel),
+        Row::Bind(Action::Menu),
+        Row::Bind(Action::Run),
+        Row::Bind(Action::Save),
+    ];
+}
+
+// The options screen, listed in the text box with a marker beside the selected
+// row and each setting as it is now. Choosing the diagonals flicks to the next
+// one, choosing an action waits for the button to bind it to. Redraw it after
+// any event that isn't closing it.
+pub struct OptionsMenu {
+    rows: Cursor,
+    listening: Option<Action>,
+    // What the config was when the menu opened, to tell if it needs saving.
+    opened_with: Config,
+}
+
+impl OptionsMenu {
+    pub fn new(text: &mut TextBox, vram: &mut VRamManager, config: &Config) -> Self {
+        let menu = Self {
+            rows: Cursor::new(Row::ALL.len()),
+            listening: None,
+            opened_with: *config,
+        };
+        menu.draw(text, vram, config);
+
+        menu
+    }
+
+    pub fn changed(&self, config: &Config) -> bool {
+        *config != self.opened_with
+    }
+
+    pub fn tick(&mut self, input: &mut Input) -> Option<MenuEvent> {
+        if let Some(action) = self.listening {
+            let (pressed, _) = BUTTONS
+                .into_iter()
+                .find(|&(button, _)| input.buttons().is_just_pressed(button))?;
+
+            input.config.bind(action, pressed);
+            self.listening = None;
+
+            // The press was to pick the button, not to do whatever it's bound to.
+            for action in Action::ALL {
+                input.take(action);
+            }
+
+            return Some(MenuEvent::Chosen(self.rows.selected()));
+        }
+
+        let event = self.rows.tick(input);
+
+        if let Some(MenuEvent::Chosen(index)) = event {
+            match Row::ALL[index] {
+                Row::Diagonal => input.config.diagonal = input.config.diagonal.next(),
+                Row::Bind(action) => self.listening = Some(action),
+            }
+        }
+
+        event
+    }
+
+    pub fn draw(&self, text: &mut TextBox, vram: &mut VRamManager, config: &Config) {
+        let lines: Vec<String> = Row::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, &row)| {
+                let marker = if index == self.rows.selected() {
+                    ">"
+                } else {
+                    " "
+                };
+                let setting = match row {
+                    Row::Diagonal => format!("Diagonals: {}", name(config.diagonal)),
+                    Row::Bind(action) if self.listening == Some(action) => {
+                        format!("{action:?}: press a button")
+                    }
+                    Row::Bind(action) => {
+                        format!("{action:?}: {}", button_name(config.binding(action)))
+                    }
+                };
+
+                format!("{marker} {setting}")
+            })
+            .collect();
+
+        text.list(vram, &lines);
+    }
+}
+
+fn name(diagonal: Diagonal) -> &'static str {
+    match diagonal {
+        Diagonal::Both => "both",
+        Diagonal::Horizontal => "horizontal",
+        Diagonal::Vertical => "vertical",
+        Diagonal::LastPressed => "last pressed",
+    }
+}
+
+// Bindings loaded from a save might not be one of the buttons on offer.
+fn button_name(binding: u16) -> &'static str {
+    BUTTONS
+        .iter()
+        .find(|&&(button, _)| button == binding)
+        .map_or("?", |&(_, name)| name)
+}
@@ -0,0 +1,115 @@
+use agb::display::{
+    object::{OamManaged, Object},
+    tiled::{
+        MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
+    },
+    Priority,
+};
+
+use logic::{
+    input::Input,
+    menu::{Cursor, MenuEvent},
+};
+
+use crate::{stamp::Stamp, tiles::CURSOR};
+
+// Where the window is stamped, in tiles from the top left of the screen.
+const WINDOW: (u16, u16) = (1, 1);
+// The cursor beside the first entry, in pixels from the top left of the screen.
+const ORIGIN: (i32, i32) = (12, 20);
+const ROW_HEIGHT: i32 = 16;
+
+// In the order they're listed in the window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Entry {
+    Items,
+    Save,
+    Options,
+    Quit,
+}
+
+impl Entry {
+    pub const ALL: [Entry; 4] = [Entry::Items, Entry::Save, Entry::Options, Entry::Quit];
+}
+
+// The Start menu. The window is stamped once into a background of its own on
+// P0, which stays hidden until the menu opens, and the cursor is a sprite on
+// top of it.
+pub struct PauseMenu<'a> {
+    window: MapLoan<'a, RegularMap>,
+    cursor: Object<'a>,
+    entries: Cursor,
+    open: bool,
+}
+
+impl<'a> PauseMenu<'a> {
+    pub fn new(
+        bg_gfx: &'a Tiled0,
+        vram: &mut VRamManager,
+        gfx: &'a OamManaged,
+        format: TileFormat,
+        stamp: Stamp,
+    ) -> Self {
+        let mut window =
+            bg_gfx.background(Priority::P0, RegularBackgroundSize::Background32x32, format);
+        stamp.draw(&mut window, vram, WINDOW);
+        window.commit(vram);
+        window.set_visible(false);
+
+        let mut cursor = gfx.object_sprite(CURSOR.sprite(0));
+        cursor.set_priority(Priority::P0).hide();
+
+        Self {
+            window,
+            cursor,
+            entries: Cursor::new(Entry::ALL.len()),
+            open: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    // Only the cursor, the window is a background.
+    pub fn objects(&self) -> usize {
+        1
+    }
+
+    // Always opens on the first entry.
+    pub fn open(&mut self) {
+        self.entries = Cursor::new(Entry::ALL.len());
+        self.cursor.set_position(ORIGIN).show();
+        self.window.set_visible(true);
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.cursor.hide();
+        self.window.set_visible(false);
+        self.open = false;
+    }
+
+    // For screens opened from the menu that need the room, the cursor stays
+    // where it was.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.window.set_visible(visible);
+
+        if visible {
+            self.cursor.show();
+        } else {
+            self.cursor.hide();
+        }
+    }
+
+    pub fn tick(&mut self, input: &mut Input) -> Option<MenuEvent> {
+        let event = self.entries.tick(input);
+
+        if let Some(MenuEvent::Moved(row)) = event {
+            self.cursor
+                .set_position((ORIGIN.0, ORIGIN.1 + row as i32 * ROW_HEIGHT));
+        }
+
+        event
+    }
+}
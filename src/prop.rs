@@ -0,0 +1,70 @@
+use agb::{
+    display::object::{OamManaged, Object, Tag},
+    fixnum::{Rect, Vector2D},
+};
+
+use logic::depth::Layer;
+
+use crate::{depth, oam::Budget, tiles::PROPS};
+
+// Scenery placed by the map's .props file. The footprint is the part that
+// blocks movement, relative to the top left of the art.
+pub struct Prop {
+    pub tag: &'static str,
+    pub position: (i32, i32),
+    pub footprint: (i32, i32, i32, i32),
+}
+
+impl Prop {
+    pub fn footprint(&self) -> Rect<i32> {
+        let (x, y, width, height) = self.footprint;
+
+        Rect::new(
+            Vector2D::from(self.position) + (x, y).into(),
+            (width, height).into(),
+        )
+    }
+}
+
+pub struct Placed<'a> {
+    // None when the prop is stamped into the backgrounds instead.
+    sprite: Option<(Object<'a>, &'static Tag)>,
+    position: Vector2D<i32>,
+    footprint: Rect<i32>,
+}
+
+impl<'a> Placed<'a> {
+    // A sprite from gfx/props.aseprite, y sorted with everything else standing
+    // on the map.
+    pub fn new(gfx: &'a OamManaged, prop: &Prop) -> Self {
+        let tag = PROPS.tags().get(prop.tag);
+
+        Self {
+            sprite: Some((gfx.object_sprite(tag.sprite(0)), tag)),
+            ..Self::stamped(prop)
+        }
+    }
+
+    // Background::load draws the art, this is only what's in the way.
+    pub fn stamped(prop: &Prop) -> Self {
+        Self {
+            sprite: None,
+            position: prop.position.into(),
+            footprint: prop.footprint(),
+        }
+    }
+
+    pub fn footprint(&self) -> Rect<i32> {
+        self.footprint
+    }
+
+    pub fn render(&mut self, scroll: Vector2D<i32>, budget: &mut Budget) {
+        let Some((sprite, tag)) = self.sprite.as_mut() else {
+            return;
+        };
+
+        if budget.show(sprite, tag.sprite(0), self.position - scroll) {
+            depth::sort(sprite, self.footprint, Layer::Prop);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use agb::save::{Error, SaveData, SaveManager};
+
+use logic::{
+    input::Config,
+    save::{self, Reader, Writer, HEADER_SIZE, SLOTS, SLOT_SIZE},
+    state::State,
+};
+
+use crate::{
+    direction::Direction,
+    item::{Bag, Item},
+    map::MAPS,
+};
+
+pub const VERSION: u16 = 1;
+
+#[derive(Clone, Copy)]
+pub struct SaveState {
+    pub map: &'static str,
+    pub scroll: (i16, i16),
+    pub facing: Direction,
+    pub config: Config,
+    pub state: State,
+    pub bag: Bag,
+    // Minute of the day on the clock.
+    pub minutes: u16,
+}
+
+// The slots are laid out in logic::save, this just reads and writes them.
+pub struct Save {
+    data: SaveData,
+    stride: usize,
+    sequence: u16,
+    next_slot: usize,
+}
+
+impl Save {
+    pub fn new(manager: &mut SaveManager) -> Result<Self, Error> {
+        manager.init_sram();
+
+        let data = manager.access()?;
+        let stride = data.align_range(0..SLOT_SIZE).end;
+
+        Ok(Self {
+            data,
+            stride,
+            sequence: 0,
+            next_slot: 0,
+        })
+    }
+
+    pub fn load(&mut self) -> Option<SaveState> {
+        let mut buffers = [[0u8; SLOT_SIZE]; SLOTS];
+        for (slot, buffer) in buffers.iter_mut().enumerate() {
+            if self.data.read(slot * self.stride, buffer).is_err() {
+                *buffer = [0; SLOT_SIZE];
+            }
+        }
+
+        // A slot that opens but doesn't decode is as good as corrupted.
+        let slots = buffers.each_ref().map(|buffer| {
+            let slot = save::open(buffer)?;
+            Some((slot.sequence, migrate(slot.version, slot.payload)?))
+        });
+
+        let newest = save::newest(&slots.map(|slot| slot.map(|(sequence, _)| sequence)))?;
+        let (sequence, state) = slots[newest]?;
+
+        self.sequence = sequence;
+        self.next_slot = (newest + 1) % SLOTS;
+
+        Some(state)
+    }
+
+    pub fn store(&mut self, state: &SaveState) -> Result<(), Error> {
+        let mut buffer = [0u8; SLOT_SIZE];
+        let length = encode(state, &mut buffer[HEADER_SIZE..]);
+        let sequence = self.sequence.wrapping_add(1);
+        let length = save::seal(&mut buffer, VERSION, sequence, length);
+
+        let offset = self.next_slot * self.stride;
+        let mut block = self.data.prepare_write(offset..offset + SLOT_SIZE)?;
+        block.write_and_verify(offset, &buffer[..length])?;
+
+        self.sequence = sequence;
+        self.next_slot = (self.next_slot + 1) % SLOTS;
+
+        Ok(())
+    }
+}
+
+// Every version the game has ever written gets decoded here. When the format
+// changes, bump VERSION, teach `encode` the new layout and add an arm that reads
+// the old one into the current SaveState.
+fn migrate(version: u16, payload: &[u8]) -> Option<SaveState> {
+    match version {
+        1 => decode(&mut Reader(payload)),
+        _ => None,
+    }
+}
+
+// The fields in the order `encode` writes them.
+fn decode(reader: &mut Reader) -> Option<SaveState> {
+    let map = MAPS.get(reader.u8()? as usize)?.name;
+    let scroll = (reader.i16()?, reader.i16()?);
+    let facing = match reader.u8()? {
+        0 => Direction::Up,
+        1 => Direction::Down,
+        2 => Direction::Left,
+        3 => Direction::Right,
+        _ => return None,
+    };
+
+    Some(SaveState {
+        map,
+        scroll,
+        facing,
+        config: reader.config()?,
+        state: reader.state()?,
+        bag: reader.inventory(&Item::ALL)?,
+        minutes: reader.u16()?,
+    })
+}
+
+fn encode(state: &SaveState, buffer: &mut [u8]) -> usize {
+    let mut writer = Writer(buffer, 0);
+
+    let map = MAPS.iter().position(|map| map.name == state.map).unwrap();
+    writer.u8(map as u8);
+    writer.i16(state.scroll.0);
+    writer.i16(state.scroll.1);
+    writer.u8(match state.facing {
+        Direction::Up => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Right => 3,
+    });
+
+    writer.config(&state.config);
+    writer.state(&state.state);
+    writer.inventory(&state.bag, &Item::ALL);
+    writer.u16(state.minutes);
+
+    writer.1
+}
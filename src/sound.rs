@@ -0,0 +1,25 @@
+// A placeholder: nothing here makes a sound. There are no samples in gfx/ and
+// agb's mixer isn't set up, so every sound is only written to the mgba log as
+// what would have played. Callers already say when and what, so hooking up the
+// mixer means swapping the logger for it here and at the call sites.
+use agb::mgba::{DebugLevel, Mgba};
+
+use logic::{menu::Sound, terrain::Footstep};
+
+// A menu sound.
+pub fn play(logger: &mut Option<Mgba>, sound: Sound) {
+    if let Some(logger) = logger {
+        let _ = logger.print(format_args!("sound: {sound:?}"), DebugLevel::Debug);
+    }
+}
+
+// A footstep as the player moves onto another tile, going by the ground.
+pub fn step(logger: &mut Option<Mgba>, footstep: Footstep) {
+    if footstep == Footstep::None {
+        return;
+    }
+
+    if let Some(logger) = logger {
+        let _ = logger.print(format_args!("step: {footstep:?}"), DebugLevel::Debug);
+    }
+}
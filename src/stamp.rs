@@ -0,0 +1,55 @@
+use core::ops::Range;
+
+use agb::display::{
+    tile_data::TileData,
+    tiled::{RegularMap, VRamManager},
+};
+
+// Name and size in tiles of every aseprite tag build.rs cut out for stamping.
+static LAYOUTS: &[(&str, (u16, u16))] = include!(concat!(env!("OUT_DIR"), "/stamps.rs"));
+
+// Multi-tile art drawn into a background rather than shown as a sprite, so it
+// costs VRAM tiles instead of OAM entries. The tiles come from
+// include_quantised_gfx alongside the map tiles, which packs their colours into
+// the same palette banks.
+pub struct Stamp {
+    size: (u16, u16),
+    tiles: &'static TileData,
+}
+
+impl Stamp {
+    pub fn new(name: &str, tiles: &'static TileData) -> Self {
+        let &(_, size) = LAYOUTS
+            .iter()
+            .find(|&&(layout, _)| layout == name)
+            .unwrap_or_else(|| panic!("{name} isn't listed in build.rs"));
+
+        Self { size, tiles }
+    }
+
+    pub fn draw(&self, bg: &mut RegularMap, vram: &mut VRamManager, position: (u16, u16)) {
+        self.draw_rows(bg, vram, position, 0..self.size.1);
+    }
+
+    // Only `rows` of the stamp, still placed as if the whole of it was drawn at
+    // `(x, y)`, for splitting one across backgrounds.
+    //
+    // Deduplicated tile data keeps a setting for every tile of the original
+    // image in reading order, so that doubles as the layout.
+    pub fn draw_rows(
+        &self,
+        bg: &mut RegularMap,
+        vram: &mut VRamManager,
+        (x, y): (u16, u16),
+        rows: Range<u16>,
+    ) {
+        let (width, height) = self.size;
+
+        for row in rows.start..rows.end.min(height) {
+            for column in 0..width {
+                let setting = self.tiles.tile_settings[(row * width + column) as usize];
+                bg.set_tile(vram, (x + column, y + row), &self.tiles.tiles, setting);
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+use mapgen::generate_state_names;
+
+generate_state_names!("gfx/state.names");
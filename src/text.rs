@@ -0,0 +1,127 @@
+use core::fmt::Write;
+
+use agb::{
+    display::{
+        font::TextRenderer,
+        palette16::Palette16,
+        tiled::{
+            DynamicTile, MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap,
+            VRamManager,
+        },
+        Font, Priority,
+    },
+    include_font,
+};
+
+use logic::text::wrap;
+
+static FONT: Font = include_font!("gfx/ark-pixel-10px-proportional-latin.ttf", 10);
+
+// The box sits on the bottom of the screen and grows upwards to fit its lines.
+// Lines of the font are two tiles tall, and there's a tile of margin all round.
+const BOX_LEFT: u16 = 1;
+const BOX_WIDTH: u16 = 28;
+const BOTTOM: u16 = 20;
+// Narrow enough that a line of wide letters still fits inside the box.
+const LINE_CHARS: usize = 32;
+// Dialogue after this is cut off, so keep script lines short.
+const LINES: usize = 3;
+
+// Text in a box along the bottom of the screen, for dialogue and the like. It
+// gets a background of its own on P0 which stays hidden while there's nothing
+// to read. The letters are drawn into tiles as they're needed, in the darkest
+// and lightest colours of the first background palette.
+pub struct TextBox<'a> {
+    bg: MapLoan<'a, RegularMap>,
+    renderer: TextRenderer<'static>,
+    paper: Option<DynamicTile<'static>>,
+    colours: (u8, u8),
+}
+
+impl<'a> TextBox<'a> {
+    pub fn new(bg_gfx: &'a Tiled0, palette: &Palette16) -> Self {
+        let mut bg = bg_gfx.background(
+            Priority::P0,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+        bg.set_visible(false);
+
+        // The first colour is transparent.
+        let brightness = |&index: &u8| {
+            let colour = palette.colour(index as usize);
+            (colour & 0x1f) + (colour >> 5 & 0x1f) + (colour >> 10 & 0x1f)
+        };
+        let ink = (1..16).min_by_key(brightness).unwrap_or(1);
+        let paper = (1..16).max_by_key(brightness).unwrap_or(1);
+
+        Self {
+            bg,
+            renderer: FONT.render_text((BOX_LEFT + 1, BOTTOM)),
+            paper: None,
+            colours: (ink, paper),
+        }
+    }
+
+    pub fn is_showing(&self) -> bool {
+        self.paper.is_some()
+    }
+
+    // Replaces whatever was showing. Dialogue always gets a box big enough for
+    // all its lines so it doesn't jump about from one line to the next.
+    pub fn show(&mut self, vram: &mut VRamManager, text: &str) {
+        self.draw(vram, LINES, wrap(text, LINE_CHARS).take(LINES));
+    }
+
+    // One entry a line, for menus.
+    pub fn list(&mut self, vram: &mut VRamManager, lines: &[impl AsRef<str>]) {
+        self.draw(vram, lines.len(), lines.iter().map(AsRef::as_ref));
+    }
+
+    fn draw<'t>(
+        &mut self,
+        vram: &mut VRamManager,
+        rows: usize,
+        lines: impl Iterator<Item = &'t str>,
+    ) {
+        self.hide(vram);
+
+        let height = rows as u16 * 2 + 2;
+        let top = BOTTOM - height;
+
+        let (ink, paper) = self.colours;
+        let tile = vram.new_dynamic_tile().fill_with(paper);
+        for y in top..BOTTOM {
+            for x in BOX_LEFT..BOX_LEFT + BOX_WIDTH {
+                self.bg
+                    .set_tile(vram, (x, y), &tile.tile_set(), tile.tile_setting());
+            }
+        }
+        self.paper = Some(tile);
+
+        self.renderer = FONT.render_text((BOX_LEFT + 1, top + 1));
+        let mut writer = self.renderer.writer(ink, paper, &mut self.bg, vram);
+        for (row, line) in lines.enumerate() {
+            if row > 0 {
+                let _ = writer.write_char('\n');
+            }
+            let _ = writer.write_str(line);
+        }
+        writer.commit();
+
+        self.bg.commit(vram);
+        self.bg.set_visible(true);
+    }
+
+    // Gives the tiles back as well, so call it before leaving the game.
+    pub fn hide(&mut self, vram: &mut VRamManager) {
+        self.bg.set_visible(false);
+        self.bg.clear(vram);
+        self.bg.commit(vram);
+        self.renderer.clear(vram);
+
+        if let Some(tile) = self.paper.take() {
+            vram.remove_dynamic_tile(tile);
+        }
+    }
+}
@@ -7,7 +7,7 @@ pub static GRAPHICS: &Graphics = include_aseprite!("gfx/char-front.aseprite");
 
 pub static BALL_GRAPHICS: &Graphics = include_aseprite!("gfx/ball.aseprite");
 
-pub static HOUSES: &Graphics = include_aseprite!("gfx/houses.aseprite");
+pub static PROPS: &Graphics = include_aseprite!("gfx/props.aseprite");
 
 pub static CHAR_FRONT: &Tag = GRAPHICS.tags().get("Char Front");
 pub static CHAR_BACK: &Tag = GRAPHICS.tags().get("Char Back");
@@ -16,4 +16,9 @@ pub static CHAR_RIGHT: &Tag = GRAPHICS.tags().get("Char Right");
 
 pub static BALL_TAG: &Tag = BALL_GRAPHICS.tags().get("Ball");
 
-pub static HOUSE_1: &Tag = HOUSES.tags().get("House-1");
+pub static ITEMS: &Graphics = include_aseprite!("gfx/items.aseprite");
+pub static DIGITS: &Graphics = include_aseprite!("gfx/digits.aseprite");
+
+pub static CURSOR: &Tag = ITEMS.tags().get("Cursor");
+pub static DIGIT_TAG: &Tag = DIGITS.tags().get("Digits");
+pub static TIMES_TAG: &Tag = DIGITS.tags().get("Times");
@@ -0,0 +1,165 @@
+use agb::{
+    display::{
+        tiled::{RegularBackgroundSize, TiledMap},
+        Priority,
+    },
+    input::ButtonController,
+    interrupt::VBlank,
+    mgba::Mgba,
+    Gba,
+};
+
+use logic::{
+    input::{Action, Config, Input},
+    menu::{Cursor, MenuEvent, Sound},
+};
+use mapgen::include_quantised_gfx;
+
+use crate::{
+    app::FADE_FRAMES,
+    effects::{Effect, Effects, Tone},
+    input::pressed,
+    sound,
+    stamp::Stamp,
+    tiles::CURSOR,
+};
+
+include_quantised_gfx!(
+    title_gfx,
+    backdrop => deduplicate "gfx/title.png",
+    prompt => deduplicate "$OUT_DIR/Press-Start.png",
+    new_game => deduplicate "$OUT_DIR/New-Game.png",
+    resume => deduplicate "$OUT_DIR/Continue.png",
+);
+
+// In tiles from the top left of the screen.
+const SCREEN_TILES: (u16, u16) = (30, 20);
+const PROMPT: (u16, u16) = (10, 15);
+const ENTRIES: (u16, u16) = (12, 14);
+// The cursor beside the first entry, in pixels.
+const ORIGIN: (i32, i32) = (80, 108);
+const ROW_HEIGHT: i32 = 16;
+const BLINK_FRAMES: u32 = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Start {
+    NewGame,
+    Continue,
+}
+
+// Shows the title until the player picks how to start, then fades it out.
+// Continue is only on offer when there's a save to carry on from. Also returns
+// how many frames the title was up for, which is as good a seed as the GBA has.
+pub fn show(gba: &mut Gba, config: Config, can_continue: bool) -> (Start, u32) {
+    let vblank = VBlank::get();
+    let mut controller = ButtonController::new();
+    let mut input = Input::new(config);
+    let mut logger = Mgba::new();
+
+    let gfx = gba.display.object.get_managed();
+    let (bg_gfx, mut vram) = gba.display.video.tiled0();
+    vram.set_background_palettes(title_gfx::PALETTES);
+
+    let format = title_gfx::backdrop.tiles.format();
+    let mut backdrop =
+        bg_gfx.background(Priority::P1, RegularBackgroundSize::Background32x32, format);
+    let mut text = bg_gfx.background(Priority::P0, RegularBackgroundSize::Background32x32, format);
+
+    // The backdrop is a whole screen, and like a stamp its settings are in
+    // reading order.
+    for y in 0..SCREEN_TILES.1 {
+        for x in 0..SCREEN_TILES.0 {
+            let setting = title_gfx::backdrop.tile_settings[(y * SCREEN_TILES.0 + x) as usize];
+            backdrop.set_tile(&mut vram, (x, y), &title_gfx::backdrop.tiles, setting);
+        }
+    }
+    backdrop.commit(&mut vram);
+    backdrop.set_visible(true);
+    text.set_visible(true);
+
+    let mut effects = Effects::new(
+        gba.display.blend.get(),
+        &[backdrop.background(), text.background()],
+    );
+    effects.start(Effect::FadeIn(Tone::Black), FADE_FRAMES);
+
+    let mut cursor = gfx.object_sprite(CURSOR.sprite(0));
+    cursor.set_priority(Priority::P0).hide();
+
+    let entries: &[Start] = if can_continue {
+        &[Start::Continue, Start::NewGame]
+    } else {
+        &[Start::NewGame]
+    };
+    let mut menu: Option<Cursor> = None;
+    let mut chosen: Option<Start> = None;
+    let mut frames: u32 = 0;
+
+    loop {
+        vblank.wait_for_vblank();
+        controller.update();
+        input.update(pressed(&controller));
+        effects.tick(&mut vram, |colour| colour);
+
+        if let Some(start) = chosen {
+            if !effects.fading() {
+                backdrop.set_visible(false);
+                text.set_visible(false);
+
+                return (start, frames);
+            }
+        } else if let Some(menu) = menu.as_mut() {
+            // Start picks an entry here rather than backing out like it does in
+            // other menus, and there's nothing to back out to.
+            let event = if input.take(Action::Menu) {
+                Some(MenuEvent::Chosen(menu.selected()))
+            } else {
+                menu.tick(&mut input)
+            }
+            .filter(|&event| event != MenuEvent::Closed);
+
+            match event {
+                Some(MenuEvent::Moved(row)) => {
+                    cursor.set_position((ORIGIN.0, ORIGIN.1 + row as i32 * ROW_HEIGHT));
+                }
+                Some(MenuEvent::Chosen(row)) => {
+                    chosen = Some(entries[row]);
+                    effects.start(Effect::FadeOut(Tone::Black), FADE_FRAMES);
+                }
+                _ => {}
+            }
+
+            if let Some(event) = event {
+                sound::play(&mut logger, event.sound());
+            }
+        } else if input.take(Action::Menu) || input.take(Action::Confirm) {
+            text.clear(&mut vram);
+            for (row, &start) in entries.iter().enumerate() {
+                let stamp = match start {
+                    Start::NewGame => Stamp::new("New-Game", &title_gfx::new_game),
+                    Start::Continue => Stamp::new("Continue", &title_gfx::resume),
+                };
+                stamp.draw(
+                    &mut text,
+                    &mut vram,
+                    (ENTRIES.0, ENTRIES.1 + row as u16 * 2),
+                );
+            }
+
+            cursor.set_position(ORIGIN).show();
+            menu = Some(Cursor::new(entries.len()));
+            sound::play(&mut logger, Sound::Choose);
+        } else if frames.is_multiple_of(BLINK_FRAMES) {
+            if (frames / BLINK_FRAMES).is_multiple_of(2) {
+                Stamp::new("Press-Start", &title_gfx::prompt).draw(&mut text, &mut vram, PROMPT);
+            } else {
+                text.clear(&mut vram);
+            }
+        }
+
+        frames = frames.wrapping_add(1);
+
+        text.commit(&mut vram);
+        gfx.commit();
+    }
+}